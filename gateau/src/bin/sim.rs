@@ -0,0 +1,179 @@
+//! Plays bots against each other in-process, as fast as possible, and reports how they did.
+//!
+//! Usage: `sim [--games N] [--hands N] [--stack N] BOT BOT...`
+//! where each BOT is one of `caller`, `minraiser`, `checkfolder` or `random`.
+
+extern crate gateau;
+
+use gateau::bot::{Bot, CallingStation, MinRaiser, CheckFolder, RandomBot};
+use gateau::events::{ChannelSink, GameEvent, Recipient};
+use gateau::game::Game;
+use std::env;
+use std::process;
+use std::sync::Arc;
+use std::time::Instant;
+
+const DEFAULT_GAMES : usize = 100;
+const DEFAULT_HANDS : usize = 1000; // Stop a game after this many hands if nobody's won
+const DEFAULT_STACK : usize = 200;
+
+/// z-score for a 95% confidence interval
+const Z_95 : f64 = 1.96;
+
+struct SimConfig {
+    games : usize,
+    hands : usize,
+    stack : usize,
+    bots  : Vec<String>,
+}
+
+fn main() {
+    let config = match parse_args(env::args().skip(1).collect()) {
+        Ok(config) => config,
+        Err(err) => {
+            println!("{}",err);
+            println!("Usage: sim [--games N] [--hands N] [--stack N] BOT BOT...");
+            println!("Bots: caller, minraiser, checkfolder, random");
+            process::exit(1);
+        },
+    };
+
+    gateau::set_logging(false);
+
+    let names = make_bots(&config.bots).unwrap().iter().map(|bot| bot.name()).collect::<Vec<_>>();
+    let mut results = vec![Vec::with_capacity(config.games); names.len()];
+
+    let started = Instant::now();
+    let mut total_hands = 0;
+    for _ in 0..config.games {
+        let bots = make_bots(&config.bots).unwrap();
+        let (chips, hands) = play_game(bots, config.hands, config.stack);
+        total_hands += hands;
+
+        for (seat, &chip_count) in chips.iter().enumerate() {
+            results[seat].push(chip_count as f64 - config.stack as f64);
+        }
+    }
+    let elapsed = started.elapsed();
+
+    println!("RESULTS - {} games, {} hands in {}.{:03}s",config.games,total_hands,
+             elapsed.as_secs(),elapsed.subsec_millis());
+    println!("{:<4} {:<16} {:>12} {:>12}","#","bot","chips/game","95% CI");
+    for (seat, name) in names.iter().enumerate() {
+        let (mean, interval) = mean_and_interval(&results[seat]);
+        println!("{:<4} {:<16} {:>+12.2} {:>12}",seat,name,mean,format!("±{:.2}",interval));
+    }
+}
+
+/// Play one game to the end, or until `max_hands` hands have been played.
+/// Returns each bot's chips at the end, and the number of hands played.
+fn play_game(mut bots : Vec<Box<dyn Bot>>, max_hands : usize, stack : usize) -> (Vec<usize>, usize) {
+    let (sink, events) = ChannelSink::new();
+    let mut game = Game::with_sink(stack, Arc::new(sink));
+    game.set_invariant_checks(true);
+
+    for bot in &bots {
+        game.add_player(&bot.name()).unwrap();
+    }
+    game.start().unwrap();
+
+    loop {
+        // The game tells everyone what happened, then asks one bot to move
+        let mut to_move = None;
+        for (recipient, event) in events.try_iter() {
+            if let Recipient::Player(id) = recipient {
+                bots[id].observe(&event);
+
+                if let GameEvent::ToMove(info) = event {
+                    if info.legal_actions.is_some() {
+                        to_move = Some(info);
+                    }
+                }
+            }
+        }
+
+        let state = game.public_state();
+        if state.game_over || state.hand_number > max_hands {
+            break;
+        }
+
+        let to_move = match to_move {
+            Some(to_move) => to_move,
+            None => panic!("Game is waiting on nobody"),
+        };
+
+        let action = bots[to_move.player_id].act(&to_move, &state);
+        game.player_action(to_move.player_id, action).unwrap();
+    }
+
+    // Anything in front of a player when we stop still belongs to them
+    let chips = (0..bots.len()).map(|id| {
+                                    let player = &game.players[&id];
+                                    player.chips + player.hand_contrib + player.street_contrib
+                                })
+                                .collect();
+    let hands = game.public_state().hand_number.min(max_hands);
+
+    (chips, hands)
+}
+
+fn make_bots(names : &[String]) -> Result<Vec<Box<dyn Bot>>, String> {
+    names.iter()
+         .map(|name| -> Result<Box<dyn Bot>, String> {
+             match name.to_lowercase().as_ref() {
+                 "caller"      => Ok(Box::new(CallingStation)),
+                 "minraiser"   => Ok(Box::new(MinRaiser)),
+                 "checkfolder" => Ok(Box::new(CheckFolder)),
+                 "random"      => Ok(Box::new(RandomBot)),
+                 other => Err(format!("Unknown bot: {}",other)),
+             }
+         })
+         .collect()
+}
+
+fn parse_args(args : Vec<String>) -> Result<SimConfig, String> {
+    let mut config = SimConfig {
+        games : DEFAULT_GAMES,
+        hands : DEFAULT_HANDS,
+        stack : DEFAULT_STACK,
+        bots  : Vec::new(),
+    };
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--games" | "--hands" | "--stack" => {
+                let value = args.next()
+                                .and_then(|value| value.parse::<usize>().ok())
+                                .ok_or(format!("{} needs a number",arg))?;
+                match arg.as_ref() {
+                    "--games" => config.games = value,
+                    "--hands" => config.hands = value,
+                    _         => config.stack = value,
+                }
+            },
+            _ => config.bots.push(arg),
+        }
+    }
+
+    if config.bots.len() < 2 {
+        return Err("Need at least two bots".to_string());
+    }
+
+    make_bots(&config.bots)?;
+
+    Ok(config)
+}
+
+/// Mean of the samples, and the half-width of its 95% confidence interval
+fn mean_and_interval(samples : &[f64]) -> (f64, f64) {
+    let n = samples.len() as f64;
+    if samples.len() < 2 {
+        return (samples.iter().sum::<f64>() / n.max(1.0), 0.0);
+    }
+
+    let mean = samples.iter().sum::<f64>() / n;
+    let variance = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0);
+
+    (mean, Z_95 * (variance / n).sqrt())
+}
@@ -0,0 +1,103 @@
+use game::{Action, GameState, LegalActions};
+use events::{GameEvent, ToMoveInfo};
+use rand::{thread_rng, Rng};
+
+/// A player that lives in the same process as the game, e.g. for simulations.
+/// Bots are told the same things players are sent over the network.
+pub trait Bot {
+    /// Name to play under
+    fn name(&self) -> String;
+
+    /// Called with everything sent to this bot's player
+    fn observe(&mut self, _event : &GameEvent) {}
+
+    /// Called when it's this bot's turn. `to_move` always has the legal actions,
+    /// and `state` is everything about the game that isn't secret.
+    fn act(&mut self, to_move : &ToMoveInfo, state : &GameState) -> Action;
+}
+
+/// How much a player would have to bet to raise to `raise_to`
+pub fn raise_by(state : &GameState, player_id : usize, raise_to : usize) -> usize {
+    let street_contrib = state.players.iter()
+                                      .find(|player| player.player_id == player_id)
+                                      .map(|player| player.street_contrib)
+                                      .unwrap_or(0);
+
+    raise_to.saturating_sub(street_contrib)
+}
+
+/// Calls anything and never raises
+pub struct CallingStation;
+
+impl Bot for CallingStation {
+    fn name(&self) -> String {
+        "CallingStation".to_string()
+    }
+
+    fn act(&mut self, _to_move : &ToMoveInfo, _state : &GameState) -> Action {
+        Action::Call
+    }
+}
+
+/// Raises as little as it can whenever it can, and calls otherwise
+pub struct MinRaiser;
+
+impl Bot for MinRaiser {
+    fn name(&self) -> String {
+        "MinRaiser".to_string()
+    }
+
+    fn act(&mut self, to_move : &ToMoveInfo, state : &GameState) -> Action {
+        match to_move.legal_actions {
+            Some(LegalActions { min_raise_to : Some(raise_to), .. }) => {
+                Action::Bet(raise_by(state, to_move.player_id, raise_to))
+            },
+            _ => Action::Call,
+        }
+    }
+}
+
+/// Checks when it's free and folds to any bet
+pub struct CheckFolder;
+
+impl Bot for CheckFolder {
+    fn name(&self) -> String {
+        "CheckFolder".to_string()
+    }
+
+    fn act(&mut self, _to_move : &ToMoveInfo, _state : &GameState) -> Action {
+        // An inappropriate check is a fold
+        Action::Check
+    }
+}
+
+/// Folds, calls or raises at random, never folding when it could check
+pub struct RandomBot;
+
+impl Bot for RandomBot {
+    fn name(&self) -> String {
+        "RandomBot".to_string()
+    }
+
+    fn act(&mut self, to_move : &ToMoveInfo, state : &GameState) -> Action {
+        let legal = match to_move.legal_actions {
+            Some(ref legal) => legal,
+            None => return Action::Call,
+        };
+
+        let mut rng = thread_rng();
+        match rng.gen_range(0, 3) {
+            0 if !legal.can_check => Action::Fold,
+            1 => {
+                match (legal.min_raise_to, legal.max_raise_to) {
+                    (Some(min), Some(max)) => {
+                        let raise_to = rng.gen_range(min, max + 1);
+                        Action::Bet(raise_by(state, to_move.player_id, raise_to))
+                    },
+                    _ => Action::Call,
+                }
+            },
+            _ => Action::Call,
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+
+/// Everything that can go wrong when playing or running a game
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameError {
+    UnknownGame,      // There's no game with that ID
+    UnknownSecret,    // No player in the game has that secret ID
+    WrongAdminToken,  // That isn't the game's admin token
+    NotStarted,       // The game hasn't started yet
+    AlreadyStarted,   // The game has already started
+    GameOver,         // The game has finished
+    NotOver,          // The game hasn't finished yet
+    NotYourTurn,      // Someone else has the action
+    TableFull,        // No more players can join
+    NotEnoughPlayers, // A game needs at least two players to start
+    AlreadyConnected, // Something else is already reading the player's messages
+    InvalidAction,    // The move isn't one we know about
+    InvalidConfig,    // The config option or its value isn't any good
+}
+
+impl GameError {
+    /// A short code for the error that won't change, for clients to match on
+    pub fn code(&self) -> &'static str {
+        match *self {
+            GameError::UnknownGame      => "unknown_game",
+            GameError::UnknownSecret    => "unknown_secret",
+            GameError::WrongAdminToken  => "wrong_admin_token",
+            GameError::NotStarted       => "not_started",
+            GameError::AlreadyStarted   => "already_started",
+            GameError::GameOver         => "game_over",
+            GameError::NotOver          => "not_over",
+            GameError::NotYourTurn      => "not_your_turn",
+            GameError::TableFull        => "table_full",
+            GameError::NotEnoughPlayers => "not_enough_players",
+            GameError::AlreadyConnected => "already_connected",
+            GameError::InvalidAction    => "invalid_action",
+            GameError::InvalidConfig    => "invalid_config",
+        }
+    }
+
+    /// A human readable description of the error
+    pub fn reason(&self) -> &'static str {
+        match *self {
+            GameError::UnknownGame      => "No such game!",
+            GameError::UnknownSecret    => "Unknown secret ID!",
+            GameError::WrongAdminToken  => "Wrong admin token!",
+            GameError::NotStarted       => "Game hasn't started!",
+            GameError::AlreadyStarted   => "Game already started!",
+            GameError::GameOver         => "Game is over!",
+            GameError::NotOver          => "Game isn't over yet!",
+            GameError::NotYourTurn      => "Not your turn!",
+            GameError::TableFull        => "No space to join this game!",
+            GameError::NotEnoughPlayers => "Not enough players to start!",
+            GameError::AlreadyConnected => "Already reading your messages somewhere else!",
+            GameError::InvalidAction    => "Invalid action!",
+            GameError::InvalidConfig    => "Bad config option!",
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.reason())
+    }
+}
@@ -0,0 +1,256 @@
+//! Everything a game tells players about, and the interface it tells them through.
+//!
+//! Each event serializes to the JSON structure players are sent, as documented in the README.
+
+use std::fmt::Debug;
+use std::sync::Mutex;
+use std::sync::mpsc::{channel, Sender, Receiver};
+use game::LegalActions;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerPrivateInfo {
+    pub info : String, // "PlayerPrivateInfo"
+    pub ingame_id : usize,
+    pub secret_id : String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GameTableInfo {
+    pub info : String, // "GameTableInfo"
+    pub starting_stack : usize,
+    pub seat_order : Vec<usize>,
+    pub button_player : usize,
+    pub display_names : Vec<(usize, String)>,
+    pub small_blind : usize,
+    pub big_blind : usize,
+    pub ante : usize,
+    pub big_blind_ante : bool,
+    pub seed_hash : String, // Commitment to the game's seed, which is revealed when it's over
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HoleCardInfo {
+    pub info : String,
+    pub hole_cards : (String, String),
+    pub hand_number : usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MoveInfo {
+    pub info : String, // "MoveInfo"
+    pub player_id : usize,
+    pub move_type : String,
+    pub value : usize,
+    pub hand_number : usize,
+    pub timeout : bool, // Was this move forced because the player ran out of time?
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UncalledBetInfo {
+    pub info : String, // "UncalledBetInfo"
+    pub player_id : usize,
+    pub amount : usize, // Chips given back, which never go in the pot
+    pub hand_number : usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToMoveInfo {
+    pub info : String,
+    pub player_id : usize,
+    pub hand_number : usize,
+    pub move_time : u64, // Seconds to move before the time bank is used (0 for no limit)
+    pub time_bank : f64, // Seconds left in the player's time bank
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub legal_actions : Option<LegalActions>, // Only sent to the player we're waiting on
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreetInfo {
+    pub info : String, // "StreetInfo"
+    pub street : String,
+    pub button_player : usize,
+    pub board_cards_revealed : Vec<String>,
+    pub hand_number : usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlindLevelInfo {
+    pub info : String, // "BlindLevelInfo"
+    pub level : usize,
+    pub small_blind : usize,
+    pub big_blind : usize,
+    pub ante : usize,
+    pub hand_number : usize,
+}
+
+/// One pot in a payout, from the main pot out
+#[derive(Debug, Clone, Serialize)]
+pub struct PotInfo {
+    pub size : usize,
+    pub eligible : Vec<usize>,        // Player IDs who could win it
+    pub winners : Vec<usize>,         // Player IDs who split it
+    pub hand : Option<String>,        // Winning hand, e.g. "Flush", if the pot was contested
+    pub shares : Vec<(usize, usize)>, // Player IDs and what they won from it, odd chips included
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PayoutInfo {
+    pub info : String,                              // "PayoutInfo"
+    pub reason : String,                            // E.g. "All others folded", "Showdown"
+    pub payouts : Vec<(usize, usize)>,              // Player IDs and payout amounts
+    pub pots : Vec<PotInfo>,                        // Main pot first, then side pots
+    pub showdown_order : Vec<usize>,                // Player IDs in the order they showed or mucked
+    pub hole_cards : Vec<(usize,(String, String))>, // Player IDs and revealed cards, in that order
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerEliminatedInfo {
+    pub info : String, // "PlayerEliminationInfo"
+    pub eliminated_player : usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GameOverInfo {
+    pub info : String, // "GameOverInfo"
+    pub winning_player : usize,
+    pub seed : u64, // Hashes to the seed_hash from GameTableInfo
+}
+
+/// Any of the messages a game sends. Serializes to the message itself.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum GameEvent {
+    PlayerPrivate(PlayerPrivateInfo),
+    GameTable(GameTableInfo),
+    HoleCards(HoleCardInfo),
+    Move(MoveInfo),
+    UncalledBet(UncalledBetInfo),
+    ToMove(ToMoveInfo),
+    Street(StreetInfo),
+    BlindLevel(BlindLevelInfo),
+    Payout(PayoutInfo),
+    PlayerEliminated(PlayerEliminatedInfo),
+    GameOver(GameOverInfo),
+}
+
+/// Where a game sends its events. These are called while the game is being played,
+/// so anything slow (like the network) should happen somewhere else.
+pub trait NotificationSink : Debug + Send + Sync {
+    /// An event for one player only
+    fn player_event(&self, player_id : usize, event : &GameEvent);
+
+    /// An event anyone can see, like a spectator. Players are sent their own copy
+    /// through `player_event`, so there's nothing to do here unless you're watching.
+    fn public_event(&self, _event : &GameEvent) {}
+
+    /// Should the game stop waiting on this player, and check or fold for them instead?
+    fn is_disconnected(&self, _player_id : usize) -> bool {
+        false
+    }
+
+    /// What kind of sink this is, for logging
+    fn name(&self) -> &'static str {
+        "custom sink"
+    }
+}
+
+/// Throws away every event
+#[derive(Debug)]
+pub struct NullSink;
+
+impl NotificationSink for NullSink {
+    fn player_event(&self, _player_id : usize, _event : &GameEvent) {}
+
+    fn name(&self) -> &'static str {
+        "NullSink"
+    }
+}
+
+/// Who an event was meant for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recipient {
+    Player(usize),
+    Public,
+}
+
+/// Sends every event down a channel, for bots and tests running in the same process
+#[derive(Debug)]
+pub struct ChannelSink {
+    sender : Mutex<Sender<(Recipient, GameEvent)>>,
+}
+
+impl ChannelSink {
+    /// Returns the sink, and the end of the channel events come out of
+    pub fn new() -> (ChannelSink, Receiver<(Recipient, GameEvent)>) {
+        let (sender, receiver) = channel();
+        (ChannelSink { sender : Mutex::new(sender) }, receiver)
+    }
+
+    fn send(&self, recipient : Recipient, event : &GameEvent) {
+        // Nobody listening is nobody's problem but theirs
+        let _ = self.sender.lock().unwrap().send((recipient, event.clone()));
+    }
+}
+
+impl NotificationSink for ChannelSink {
+    fn player_event(&self, player_id : usize, event : &GameEvent) {
+        self.send(Recipient::Player(player_id), event);
+    }
+
+    fn public_event(&self, event : &GameEvent) {
+        self.send(Recipient::Public, event);
+    }
+
+    fn name(&self) -> &'static str {
+        "ChannelSink"
+    }
+}
+
+/// Keeps every event, in order, to be looked at afterwards
+#[derive(Debug, Default)]
+pub struct RecordingSink {
+    events : Mutex<Vec<(Recipient, GameEvent)>>,
+}
+
+impl RecordingSink {
+    pub fn new() -> RecordingSink {
+        RecordingSink::default()
+    }
+
+    /// Everything recorded so far
+    pub fn events(&self) -> Vec<(Recipient, GameEvent)> {
+        self.events.lock().unwrap().clone()
+    }
+
+    /// Everything sent to one player so far
+    pub fn player_events(&self, player_id : usize) -> Vec<GameEvent> {
+        self.recorded_for(Recipient::Player(player_id))
+    }
+
+    /// Everything anyone could see so far
+    pub fn public_events(&self) -> Vec<GameEvent> {
+        self.recorded_for(Recipient::Public)
+    }
+
+    fn recorded_for(&self, recipient : Recipient) -> Vec<GameEvent> {
+        self.events.lock().unwrap()
+                   .iter()
+                   .filter(|&&(to, _)| to == recipient)
+                   .map(|(_, event)| event.clone())
+                   .collect()
+    }
+}
+
+impl NotificationSink for RecordingSink {
+    fn player_event(&self, player_id : usize, event : &GameEvent) {
+        self.events.lock().unwrap().push((Recipient::Player(player_id), event.clone()));
+    }
+
+    fn public_event(&self, event : &GameEvent) {
+        self.events.lock().unwrap().push((Recipient::Public, event.clone()));
+    }
+
+    fn name(&self) -> &'static str {
+        "RecordingSink"
+    }
+}
@@ -0,0 +1,2030 @@
+use player::Player;
+use error::GameError;
+use events::*;
+use std::collections::BTreeMap;
+use rand::{thread_rng, Rng, SeedableRng, ChaChaRng};
+use sha2::{Sha256, Digest};
+use std::time::{Duration, Instant};
+use std::sync::Arc;
+use rs_poker::core::{Card, Rank, Value, Suit};
+use uuid::Uuid;
+
+#[derive(Debug)]
+#[derive(PartialEq)]
+pub enum Street {
+    PreFlop,
+    Flop,
+    Turn,
+    River,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    Fold,
+    Check,
+    Bet(usize),
+    PostBlind(usize),
+    PostAnte(usize),
+    Call,
+    AllIn,
+}
+
+/// A single level of the blind schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlindLevel {
+    pub small_blind : usize,
+    pub big_blind : usize,
+    #[serde(default)]
+    pub ante : usize,
+}
+
+/// A move as it was received, before it was interpreted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedMove {
+    pub player_id : usize,
+    pub action : Action,
+    pub timeout : bool, // Was the move made for the player, because of the clock or a disconnection?
+}
+
+/// Everything needed to replay a game exactly: how it was set up when it started,
+/// its seed, who played, and every move they made
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub seed : u64,
+    pub players : Vec<String>,          // Display names, by player ID
+    pub starting_stack : usize,
+    pub small_blind : usize,
+    pub big_blind : usize,
+    pub ante : usize,
+    pub big_blind_ante : bool,
+    pub blind_schedule : Vec<BlindLevel>,
+    pub move_time : u64,
+    pub time_bank : u64,
+    pub bank_refill : u64,
+    pub bank_refill_hands : usize,
+    #[serde(default)]
+    pub always_show : Vec<usize>,       // Players who show their hand at every showdown
+    pub level_ups : Vec<usize>,         // Hands the blinds went up on
+    pub moves : Vec<LoggedMove>,
+}
+
+/// A pot and who can win it
+#[derive(Debug, Clone, Serialize)]
+pub struct Pot {
+    pub size : usize,
+    pub eligible : Vec<usize>, // Players still in the hand who've put in enough to win it
+}
+
+/// What the player to act can do. Raises are given as the total the player would have
+/// put in on this street, so a raise to `x` is a `Bet(x - street_contrib)`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LegalActions {
+    pub player_id : usize,
+    pub can_check : bool,
+    pub call_amount : usize,           // Chips it takes to call, or to go all-in if that's less
+    pub min_raise_to : Option<usize>,  // Smallest legal raise, or an all-in if that's less
+    pub max_raise_to : Option<usize>,  // All-in. Both are None if the player can't raise.
+}
+
+/// What everyone at the table can see about a player
+#[derive(Debug, Serialize)]
+pub struct PlayerState {
+    pub player_id : usize,
+    pub display_name : String,
+    pub chips : usize,
+    pub street_contrib : usize,
+    pub folded : bool,
+    pub all_in : bool,
+    pub eliminated : bool,
+}
+
+/// What everyone at the table can see about the game
+#[derive(Debug, Serialize)]
+pub struct GameState {
+    pub started : bool,
+    pub game_over : bool,
+    pub hand_number : usize,
+    pub street : String,
+    pub board : Vec<String>,         // Community cards dealt so far
+    pub button_player : Option<usize>,
+    pub to_act : Option<usize>,      // Player we're waiting on, if any
+    pub current_bet : usize,
+    pub small_blind : usize,
+    pub big_blind : usize,
+    pub ante : usize,
+    pub pots : Vec<Pot>,
+    pub players : Vec<PlayerState>,  // In seat order
+}
+
+#[derive(Debug)]
+/// Contains the state of the poker game, including players, cards, action, etc.
+pub struct Game {
+    // TODO:
+    //  - Push game moves to a database
+    //  - Restart Game
+    // Possible Extensions (unnecessarily advanced)
+    //  - Game consisting of multiple tables w/ appropriate table breaks
+    
+    // Public fields
+    pub board : Vec<Card>,                // Community cards
+    pub players : BTreeMap<usize, Player>, // Players in the game by ID (see player.rs)
+    pub seat_order : Vec<usize>,          // Positions of players around the table
+                                          // N.B. seat_order[0] is button
+    pub street : Street,                  // Street we're currently on
+    pub to_act : usize,                   // Which player has action
+
+    // Private Fields
+    deck : Vec<Card>,                     // A deck of cards, dealt from the end
+    seed : u64,                           // Decides the seating and every shuffle
+    rng : ChaChaRng,                      // Seeded from `seed`
+    record : Option<GameRecord>,          // How to replay the game, from when it starts
+    replay_level_ups : Option<Vec<usize>>,// When replaying, the hands the blinds went up on
+    #[cfg(test)]
+    stacked_deck : Option<(Vec<Vec<Card>>, Vec<Card>)>, // Hole cards by seat and board for next hand
+
+    total_chips : usize,                  // Chips in play, which never changes once we start
+    invariant_checks : bool,              // Check the invariants after every move?
+
+    started : bool,                       // Has the game started?
+    game_over : bool,                     // Is the game over?
+
+    num_players : usize,                  // Player count
+    num_in_play : usize,                  // Number of players able to act (not folded or all-in)
+    num_folded  : usize,                  // Number of players who have folded
+    num_eliminated : usize,               // Number of players who have gone to 0 chips
+
+    // configurable
+    max_players : usize,                  // Maximum number of players that can join
+    starting_stack : usize,               // Number of chips we start with
+    small_blind : usize,                  // Current small blind
+    big_blind : usize,                    // Current big blind
+    ante : usize,                         // Current ante (paid by the big blind alone if big_blind_ante)
+    big_blind_ante : bool,                // Does the big blind post the ante for the whole table?
+    blind_schedule : Vec<BlindLevel>,     // Upcoming blind levels, in order
+    level_hands : usize,                  // Hands per blind level (0 to not advance by hands)
+    level_minutes : u64,                  // Minutes per blind level (0 to not advance by time)
+    move_time : u64,                      // Seconds a player has to move (0 for no limit)
+    time_bank : u64,                      // Seconds of extra time each player starts with
+    bank_refill : u64,                    // Seconds added back to each time bank...
+    bank_refill_hands : usize,            // ...every this many hands (0 for never)
+
+    button : usize,                       // Position of the dealer button
+    small_blind_seat : usize,             // Player whose seat the small blind was due from last hand
+    big_blind_seat : usize,               // Player who posted the big blind last hand
+
+    current_bet : usize,
+    min_raise   : usize,
+    last_aggressor : Option<usize>,       // Last player to bet or raise this street
+
+    hand_number : usize,
+
+    admin_token : Uuid,                   // Secret needed to administer the game
+
+    blind_level : usize,                  // Current blind level, counting up from 1
+    level_start_hand : usize,             // Hand number the current blind level started on
+    level_start_time : Instant,           // Time the current blind level started
+
+    move_started : Option<Instant>,       // When the player to act was asked to move
+
+    sink : Arc<dyn NotificationSink>,     // Where everything we tell players goes
+}
+
+impl Game {
+    /// Returns a new game object that doesn't tell anyone what happens
+    pub fn new(stack : usize) -> Game {
+        Game::with_sink(stack, Arc::new(NullSink))
+    }
+
+    /// Returns a new game object that sends everything it tells players to `sink`
+    pub fn with_sink(stack : usize, sink : Arc<dyn NotificationSink>) -> Game {
+        let seed = thread_rng().gen::<u64>();
+
+        Game{
+            deck : Vec::new(),
+            seed,
+            rng : seeded_rng(seed),
+            record : None,
+            replay_level_ups : None,
+            #[cfg(test)]
+            stacked_deck : None,
+            total_chips : 0,
+            invariant_checks : false,
+            board : Vec::new(),
+            players : BTreeMap::new(),
+            max_players : 10,
+            starting_stack : stack,
+            small_blind : 1,
+            big_blind : 2,
+            ante : 0,
+            big_blind_ante : false,
+            blind_schedule : Vec::new(),
+            level_hands : 0,
+            level_minutes : 0,
+            move_time : 0,
+            time_bank : 0,
+            bank_refill : 0,
+            bank_refill_hands : 0,
+            seat_order : Vec::new(),
+            game_over : false,
+            num_players : 0,
+            num_in_play : 0,
+            num_folded : 0,
+            num_eliminated : 0,
+            started : false,
+            button : 0,
+            small_blind_seat : 0,
+            big_blind_seat : 0,
+            street : Street::River,
+            to_act : 0,
+            //game_id : Uuid::new_v4(),
+            current_bet : 0,
+            min_raise : 2,
+            last_aggressor : None,
+            sink,
+            hand_number : 0,
+            admin_token : Uuid::new_v4(),
+            blind_level : 1,
+            level_start_hand : 1,
+            level_start_time : Instant::now(),
+            move_started : None,
+        }
+    } // pub fn with_sink
+
+    /// The secret needed to administer this game
+    pub fn admin_token(&self) -> Uuid {
+        self.admin_token
+    }
+
+    /// Is this the game's admin token?
+    pub fn is_admin(&self, token : &Uuid) -> bool {
+        *token == self.admin_token
+    }
+
+    /// "waiting" for players, "running" or "over"
+    pub fn status(&self) -> &'static str {
+        if self.game_over {
+            "over"
+        } else if self.started {
+            "running"
+        } else {
+            "waiting"
+        }
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.game_over
+    }
+
+    pub fn max_players(&self) -> usize {
+        self.max_players
+    }
+
+    /// Send everything this game tells players to `sink` instead.
+    ///
+    /// Only the game sees the new sink, so anything else holding the old one keeps using it.
+    /// The server's `Table` delivers through the `HttpSink` its game was created with, so server
+    /// games must not be given another sink.
+    pub fn set_notification_sink(&mut self, sink : Arc<dyn NotificationSink>) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        log!("CONFIG - Setting notification sink to {}",sink.name());
+        self.sink = sink;
+
+        Ok(())
+    }
+
+    /// Seed the seating and every shuffle, so the game can be played again with the same cards
+    pub fn set_seed(&mut self, seed : u64) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        self.seed = seed;
+        self.rng = seeded_rng(seed);
+        log!("CONFIG - Setting seed");
+
+        Ok(())
+    }
+
+    pub fn set_starting_stack(&mut self, stack: usize) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        self.starting_stack = stack;
+        // Anyone who's already joined starts with the new stack too
+        for player in self.players.values_mut() {
+            player.chips = stack;
+        }
+        log!("CONFIG - Setting starting stack to {}",stack);
+
+        Ok(())
+    }
+
+    pub fn set_player_limit(&mut self, limit : usize) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        // Can't kick out players that have already joined
+        if limit < self.num_players {
+            return Err(GameError::InvalidConfig);
+        }
+
+        self.max_players = limit;
+        log!("CONFIG - Setting player limit to {}",limit);
+
+        Ok(())
+    }
+
+    /// The small blind can't be more than the big blind, so set the big blind first when raising both
+    pub fn set_small_blind(&mut self, blind : usize) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        if blind > self.big_blind {
+            return Err(GameError::InvalidConfig);
+        }
+
+        self.small_blind = blind;
+        log!("CONFIG - Setting small blind to {}",blind);
+
+        Ok(())
+    }
+
+    /// The big blind can't be 0, or less than the small blind
+    pub fn set_big_blind(&mut self, blind : usize) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        if blind == 0 || blind < self.small_blind {
+            return Err(GameError::InvalidConfig);
+        }
+
+        self.big_blind = blind;
+        self.min_raise = blind;
+        log!("CONFIG - Setting big blind to {}",blind);
+
+        Ok(())
+    }
+
+    pub fn set_ante(&mut self, ante : usize) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        self.ante = ante;
+        log!("CONFIG - Setting ante to {}",ante);
+
+        Ok(())
+    }
+
+    /// With a big blind ante, the big blind posts the whole ante on behalf of the table
+    pub fn set_big_blind_ante(&mut self, big_blind_ante : bool) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        self.big_blind_ante = big_blind_ante;
+        log!("CONFIG - Setting big blind ante to {}",big_blind_ante);
+
+        Ok(())
+    }
+
+    /// Set the blind levels that follow the current blinds, in the order they're played.
+    /// Each level's blinds follow the same rules as the starting blinds.
+    pub fn set_blind_schedule(&mut self, levels : Vec<BlindLevel>) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        if levels.iter().any(|level| level.big_blind == 0 || level.small_blind > level.big_blind) {
+            return Err(GameError::InvalidConfig);
+        }
+
+        log!("CONFIG - Setting blind schedule to {:?}",levels);
+        self.blind_schedule = levels;
+
+        Ok(())
+    }
+
+    /// Advance the blind level every `hands` hands. 0 disables hand-count levels.
+    pub fn set_level_hands(&mut self, hands : usize) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        self.level_hands = hands;
+        log!("CONFIG - Setting blind level length to {} hands",hands);
+
+        Ok(())
+    }
+
+    /// Advance the blind level every `minutes` minutes. 0 disables timed levels.
+    pub fn set_level_minutes(&mut self, minutes : u64) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        self.level_minutes = minutes;
+        log!("CONFIG - Setting blind level length to {} minutes",minutes);
+
+        Ok(())
+    }
+
+    /// Give players `seconds` to make each move before they're checked or folded. 0 for no limit.
+    pub fn set_move_time(&mut self, seconds : u64) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        self.move_time = seconds;
+        log!("CONFIG - Setting move time to {} seconds",seconds);
+
+        Ok(())
+    }
+
+    /// Give every player a bank of `seconds` to use once their move time runs out
+    pub fn set_time_bank(&mut self, seconds : u64) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        self.time_bank = seconds;
+        log!("CONFIG - Setting time bank to {} seconds",seconds);
+
+        Ok(())
+    }
+
+    /// Add `seconds` back to each time bank (up to its starting size) every so often
+    pub fn set_bank_refill(&mut self, seconds : u64) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        self.bank_refill = seconds;
+        log!("CONFIG - Setting time bank refill to {} seconds",seconds);
+
+        Ok(())
+    }
+
+    /// Refill time banks every `hands` hands. 0 to never refill.
+    pub fn set_bank_refill_hands(&mut self, hands : usize) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        self.bank_refill_hands = hands;
+        log!("CONFIG - Setting time bank refill every {} hands",hands);
+
+        Ok(())
+    }
+
+    /// Add a player to the game. Things like ID, starting stack, etc are handled automatically. 
+    /// Returns the new player's ID.
+    pub fn add_player(&mut self, name : &str) -> Result<usize, GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        if self.num_players == self.max_players {
+            return Err(GameError::TableFull);
+        }
+
+        let id = self.num_players;
+        self.players.insert(
+            id,
+            Player::new(String::from(name), self.starting_stack)
+        );
+        self.seat_order.push(id);
+
+        self.num_players += 1;
+
+        log!("DEBUG - Added player {}:{}",id,name);
+
+        Ok(id)
+    } // pub fn add_player
+
+    /// Have a player show their hand at every showdown, even when they could muck it
+    pub fn set_always_show(&mut self, player_id : usize, always_show : bool) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        let player = self.players.get_mut(&player_id).ok_or(GameError::InvalidConfig)?;
+        player.always_show = always_show;
+        log!("CONFIG - Player {} always shows: {}",player.display_name,always_show);
+
+        Ok(())
+    }
+
+    /// Takes a player action and applies it to the game
+    /// 
+    /// # Valid actions
+    /// * Bet(amount)
+    ///   This is a basic action that can cover calling, betting, raising.
+    ///   This action moves a number of chips from your stack to the table in front of you.
+    ///   In this way, if you bet 10, someone else raises to 30, then Bet(20) is a call. 
+    ///   An under-call is interpreted as a call.
+    ///   A raise under the min-raise is a min-raise.
+    ///   Any bet that puts you above all-in just puts you all-in.
+    ///   Bet(0) is a special case and is a Check if it's legal, otherwise it's a Fold. 
+    /// * Check
+    ///   This is a basic action that just checks if it's legal to, otherwise it folds.
+    /// * Fold
+    ///   This folds your hand.
+    /// * Call
+    ///   This is a special action that bets to match the current bet, or just checks.
+    /// * AllIn
+    ///   This puts you all in. 
+    /// * PostAnte(amount)
+    ///   Used by the game to take an ante. This doesn't move the action on.
+    ///
+    /// `player_id` is the player making the move, as found by `authenticate`.
+    pub fn player_action(&mut self, player_id: usize, recv_action: Action) -> Result<(), GameError> {
+        if !self.started {
+            return Err(GameError::NotStarted);
+        }
+
+        if self.game_over {
+            return Err(GameError::GameOver);
+        }
+
+        if player_id != self.to_act {
+            return Err(GameError::NotYourTurn);
+        }
+
+        // Only the game gets to take blinds and antes
+        match recv_action {
+            Action::PostBlind(_) | Action::PostAnte(_) => return Err(GameError::InvalidAction),
+            _ => (),
+        }
+
+        self.log_move(player_id, recv_action, false);
+        self.take_action(recv_action, false);
+        self.assert_invariants();
+
+        Ok(())
+    }
+
+    /// Play a recorded game again, move for move, telling `sink` everything as it happens.
+    /// It all comes out the same as the first time, apart from players' secret IDs
+    /// and how much time they had.
+    pub fn replay(record : &GameRecord, sink : Arc<dyn NotificationSink>) -> Result<Game, GameError> {
+        let mut game = Game::with_sink(record.starting_stack, sink);
+
+        game.set_seed(record.seed)?;
+        game.set_player_limit(record.players.len())?;
+        game.set_big_blind(record.big_blind)?;
+        game.set_small_blind(record.small_blind)?;
+        game.set_ante(record.ante)?;
+        game.set_big_blind_ante(record.big_blind_ante)?;
+        game.set_blind_schedule(record.blind_schedule.clone())?;
+        game.set_move_time(record.move_time)?;
+        game.set_time_bank(record.time_bank)?;
+        game.set_bank_refill(record.bank_refill)?;
+        game.set_bank_refill_hands(record.bank_refill_hands)?;
+        game.replay_level_ups = Some(record.level_ups.clone());
+
+        for name in &record.players {
+            game.add_player(name)?;
+        }
+        for &id in &record.always_show {
+            game.set_always_show(id, true)?;
+        }
+
+        game.start()?;
+
+        for logged in &record.moves {
+            if !logged.timeout {
+                game.player_action(logged.player_id, logged.action)?;
+            } else if logged.player_id != game.to_act || game.game_over {
+                return Err(GameError::NotYourTurn);
+            } else {
+                game.log_move(logged.player_id, logged.action, true);
+                game.take_action(logged.action, true);
+            }
+        }
+
+        Ok(game)
+    }
+
+    /// How to replay the game. The game has to have started, and this gives away the seed,
+    /// so it's best kept secret until the game is over.
+    pub fn record(&self) -> Option<&GameRecord> {
+        self.record.as_ref()
+    }
+
+    /// Keep a move in the record, so the game can be replayed
+    fn log_move(&mut self, player_id : usize, action : Action, timeout : bool) {
+        if let Some(ref mut record) = self.record {
+            record.moves.push(LoggedMove {
+                player_id,
+                action,
+                timeout,
+            });
+        }
+    }
+
+    /// Check the move timer, and if the player to act has run out of time or can't be reached,
+    /// check or fold for them
+    pub fn tick(&mut self) {
+        if !self.started || self.game_over || self.move_started.is_none() {
+            return;
+        }
+
+        if self.sink.is_disconnected(self.to_act) {
+            log!("GAME - Player {} is disconnected",self.players[&self.to_act].display_name);
+            let to_act = self.to_act;
+            self.log_move(to_act, Action::Check, true);
+            self.take_action(Action::Check, true);
+            self.assert_invariants();
+            return;
+        }
+
+        // Players get their move time, then their time bank
+        let timed_out = match self.move_started {
+            Some(started) if self.move_time > 0 => {
+                let allowed = Duration::from_secs(self.move_time) + self.players[&self.to_act].time_bank;
+                started.elapsed() >= allowed
+            },
+            _ => false,
+        };
+
+        if timed_out {
+            log!("GAME - Player {} ran out of time",self.players[&self.to_act].display_name);
+            let to_act = self.to_act;
+            self.log_move(to_act, Action::Check, true);
+            self.take_action(Action::Check, true);
+            self.assert_invariants();
+        }
+    }
+
+    /// Check after every move, panicking if anything's wrong. Off by default: a server can't
+    /// have one bad hand take every other game down with it.
+    pub fn set_invariant_checks(&mut self, on : bool) {
+        self.invariant_checks = on;
+    }
+
+    /// Check the things that should be true whenever the game is waiting on a move:
+    ///  - no chips have been made or lost
+    ///  - no stack has gone negative (which a `usize` shows by wrapping around to something huge)
+    ///  - the player to act is still in the hand and has chips to act with
+    ///  - the bet to call is the biggest contribution this street (preflop, at least the big
+    ///    blind, since a short big blind still has to be called in full)
+    ///
+    /// Returns what's wrong if anything is.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if !self.started {
+            return Ok(());
+        }
+
+        let mut total = 0;
+        for (id, player) in &self.players {
+            let in_front = player.chips + player.hand_contrib + player.street_contrib;
+            if in_front > self.total_chips {
+                return Err(format!("Player {} has {} chips but only {} are in play", id, in_front, self.total_chips));
+            }
+            if player.eliminated && in_front > 0 {
+                return Err(format!("Player {} is eliminated but has {} chips", id, in_front));
+            }
+            total += in_front;
+        }
+        if total != self.total_chips {
+            return Err(format!("{} chips in play, should be {}", total, self.total_chips));
+        }
+
+        if self.game_over {
+            return Ok(());
+        }
+
+        let to_act = match self.players.get(&self.to_act) {
+            Some(player) => player,
+            None => return Err(format!("Player {} is to act but isn't in the game", self.to_act)),
+        };
+        if to_act.folded || to_act.all_in || to_act.eliminated {
+            return Err(format!("Player {} is to act but can't (folded {}, all-in {}, eliminated {})",
+                               self.to_act, to_act.folded, to_act.all_in, to_act.eliminated));
+        }
+
+        let biggest_contrib = self.players.values().map(|player| player.street_contrib).max().unwrap_or(0);
+        let expected_bet = if self.street == Street::PreFlop {
+            biggest_contrib.max(self.big_blind)
+        } else {
+            biggest_contrib
+        };
+        if self.current_bet != expected_bet {
+            return Err(format!("Current bet is {} but should be {}", self.current_bet, expected_bet));
+        }
+
+        Ok(())
+    }
+
+    fn assert_invariants(&self) {
+        if !self.invariant_checks {
+            return;
+        }
+
+        if let Err(broken) = self.check_invariants() {
+            panic!("Invariant broken in hand {}: {}", self.hand_number, broken);
+        }
+    }
+
+    fn take_action(&mut self, recv_action: Action, timeout: bool) {
+        // Whatever happens, the clock stops for this player. Any time over their
+        // move time comes out of their time bank.
+        if let Some(started) = self.move_started.take() {
+            if self.move_time > 0 {
+                let plyr = self.players.get_mut(&self.to_act).unwrap();
+                if let Some(overtime) = started.elapsed().checked_sub(Duration::from_secs(self.move_time)) {
+                    plyr.time_bank = plyr.time_bank.checked_sub(overtime).unwrap_or(Duration::from_secs(0));
+                    log!("DEBUG - Player {} has {:?} left in their time bank",plyr.display_name,plyr.time_bank);
+                }
+            }
+        }
+
+        let real_action;
+        let move_type;
+        let mut move_amnt = 0;
+
+        { // Block to contain mutable borrow in plyr
+            let plyr = self.players.get_mut(&self.to_act).unwrap();
+            // Interpret the recieved action into a legal action
+            match recv_action {
+                Action::Check => {
+                    if self.current_bet > plyr.street_contrib {
+                        // Player didn't contribute enough for a check to be valid
+                        log!("DEBUG - Player {} cannot check! Folding hand...",plyr.display_name);
+                        real_action = Action::Fold;
+                    } else {
+                        real_action = Action::Check;
+                    }
+                },
+                Action::Fold => {
+                    // We can always fold
+                    real_action = Action::Fold;
+                },
+                Action::Call => {
+                    if self.current_bet == plyr.street_contrib {
+                        // If we can just check this is a check!
+                        real_action = Action::Check;
+                    } else {
+                        // Try to call, up to an all-in
+                        real_action = Action::Bet(plyr.chips.min(self.current_bet
+                                                                - plyr.street_contrib));
+                    }
+                },
+                Action::AllIn => {
+                    real_action = Action::Bet(plyr.chips);
+                }
+                Action::Bet(bet) => {
+                    if bet == 0 {
+                        if self.current_bet == 0 || (plyr.has_option 
+                                                     && self.current_bet == plyr.street_contrib) {
+                            log!("DEBUG - Player {} bet(0) interpreted as check.",plyr.display_name);
+                            real_action = Action::Check;
+                        } else {
+                            log!("DEBUG - Player {} bet(0) interpreted as fold.",plyr.display_name);
+                            real_action = Action::Fold;
+                        }
+                    } else if bet + plyr.street_contrib == self.current_bet {
+                        // This is a call
+                        real_action = Action::Bet(plyr.chips.min(bet));
+                    } else if bet + plyr.street_contrib < self.current_bet {
+                        // Sub-call is a call or all-in
+                        log!("DEBUG - Player {} tried to bet {}, not enough for a call!",plyr.display_name,bet);
+                        real_action = Action::Bet(plyr.chips.min(self.current_bet 
+                                                                 - plyr.street_contrib))
+                    } else {
+                        // Player is trying to raise
+                        if bet + plyr.street_contrib - self.current_bet < self.min_raise {
+                            // Under-raise is a min-raise or all-in
+                            log!("DEBUG - Player {} tried to under-raise by {}!",plyr.display_name,bet + plyr.street_contrib - self.current_bet);
+                            real_action = Action::Bet(plyr.chips.min(self.current_bet 
+                                                      + self.min_raise - plyr.street_contrib));
+                        } else {
+                            // Valid raise. Use it or all-in
+                            real_action = Action::Bet(plyr.chips.min(bet));
+                        }
+                    }
+                },
+                Action::PostBlind(blind) => {
+                    // Post as much of the blind as possible
+                    real_action = Action::PostBlind(plyr.chips.min(blind))
+                },
+                Action::PostAnte(ante) => {
+                    // Post as much of the ante as possible
+                    real_action = Action::PostAnte(plyr.chips.min(ante))
+                },
+            }
+
+            match real_action {
+                Action::Check => {
+                    move_type = "Check";
+                    log!("GAME - Player {} checks",plyr.display_name);
+                    plyr.has_option = false;
+                },
+                Action::Fold => {
+                    move_type = "Fold";
+                    plyr.folded = true;
+                    log!("GAME - Player {} folds",plyr.display_name);
+                    plyr.has_option = false;
+                },
+                Action::Bet(bet) => {
+                    move_type = "Bet";
+                    move_amnt = bet;
+                    if bet + plyr.street_contrib == self.current_bet {
+                        log!("GAME - Player {} calls {} (total {})",plyr.display_name, bet, bet + plyr.street_contrib);
+                    } else if self.current_bet == 0 || (self.street == Street::PreFlop && self.current_bet == self.big_blind) {
+                        log!("GAME - Player {} bets {} (total {})",plyr.display_name, bet, bet + plyr.street_contrib);
+                    } else {
+                        log!("GAME - Player {} raises {} (total {})",plyr.display_name, bet, bet + plyr.street_contrib);
+                    }
+
+                    if bet + plyr.street_contrib > self.current_bet {
+                        self.min_raise = bet + plyr.street_contrib - self.current_bet;
+                        self.last_aggressor = Some(self.to_act);
+                        log!("DEBUG - Increasing minimum raise to {}",self.min_raise);
+                    }
+
+                    self.current_bet = self.current_bet.max(bet + plyr.street_contrib);
+
+                    plyr.street_contrib += bet;
+                    plyr.chips -= bet;
+                    plyr.has_option = false;
+                },
+                Action::PostBlind(blind) => {
+                    move_type = "Blind";
+                    move_amnt = blind;
+                    plyr.street_contrib += blind;
+                    plyr.chips -= blind;
+                    log!("GAME - Player {} posts blind {}",plyr.display_name, blind);
+                },
+                Action::PostAnte(ante) => {
+                    move_type = "Ante";
+                    move_amnt = ante;
+                    // Antes are dead money: they go straight into the pot and don't count
+                    // towards calling a bet
+                    plyr.hand_contrib += ante;
+                    plyr.ante_contrib += ante;
+                    plyr.chips -= ante;
+                    log!("GAME - Player {} posts ante {}",plyr.display_name, ante);
+                },
+                _ => {
+                    panic!("Invalid action got here");
+                }
+            }
+
+            if plyr.chips == 0 {
+                plyr.all_in = true;
+                log!("GAME - Player {} has gone all-in!",plyr.display_name);
+            }
+        } // End of block to free mutable borrow
+
+        let move_info = MoveInfo {
+            info : "MoveInfo".to_string(),
+            player_id : self.to_act,
+            move_type : move_type.to_string(),
+            value : move_amnt,
+            hand_number : self.hand_number,
+            timeout,
+        };
+
+        self.send_to_all_players(GameEvent::Move(move_info));
+
+        // Antes and blinds are taken before the action starts
+        match recv_action {
+            Action::PostAnte(_) | Action::PostBlind(_) => return,
+            _ => (),
+        }
+        
+        if self.is_hand_over() {
+            self.end_hand();
+        } else if self.is_betting_over() {
+            self.run_out();
+        } else if self.is_street_over() {
+            self.next_street();
+        } else {
+            self.to_act = self.next_player(self.to_act);
+            self.request_move();
+        }
+    }
+
+    fn is_hand_over(&self) -> bool {
+        // If we're on the river and the street is done, the hand is over.
+        if self.street == Street::River && self.is_street_over() {
+            return true;
+        }
+
+        let players_with_action = self.players.iter()
+                                              .fold(0, |sum, (_, player)| {
+                                                if !player.folded && !player.eliminated {// && !player.all_in {
+                                                    sum + 1
+                                                } else {
+                                                    sum
+                                                }
+                                              });
+
+        if players_with_action == 1 {
+            log!("DEBUG - HAND OVER");
+            return true;
+        }
+
+        false
+    }
+
+    /// Is there nobody left to bet against? Once at most one player in the hand has chips
+    /// behind, and they've put in as much as anyone else, nobody else gets to act this hand.
+    fn is_betting_over(&self) -> bool {
+        let mut can_bet = self.players.values()
+                                      .filter(|player| !player.folded && !player.eliminated && !player.all_in);
+
+        match (can_bet.next(), can_bet.next()) {
+            (None, _) => true,
+            (Some(last), None) => {
+                // They still have to call an all-in that's bigger than their bet
+                let biggest_contrib = self.players.values()
+                                                  .filter(|player| !player.folded && !player.eliminated)
+                                                  .map(|player| player.street_contrib)
+                                                  .max()
+                                                  .unwrap_or(0);
+                last.street_contrib >= biggest_contrib
+            },
+            _ => false,
+        }
+    }
+
+    /// Deal the rest of the board without asking anyone to act, then pay out
+    fn run_out(&mut self) {
+        log!("DEBUG - RUNNING OUT THE BOARD");
+        while self.street != Street::River {
+            self.deal_next_street();
+        }
+
+        self.end_hand();
+    }
+
+    fn is_street_over(&self) -> bool {
+        // If anyone has option we can't end the street
+        if self.players.iter().any(|(_, player)| !player.all_in && !player.folded && !player.eliminated && player.has_option) {
+            return false;
+        }
+
+        // Otherwise, if everyone's put in the same amount we're done
+        if self.players.iter().any(|(_,player)| !player.folded && player.street_contrib != self.current_bet && !player.all_in){
+            false
+        } else {
+            log!("DEBUG - STREET OVER");
+            true
+        }
+    }
+
+    fn next_street(&mut self) {
+        if self.street == Street::River {
+            log!("GAME - New Hand!");
+            self.new_hand();
+            return;
+        }
+
+        self.deal_next_street();
+
+        // Give option to the player that will act LAST!
+        self.to_act = self.next_player(self.seat_order[0]);
+        let option_player = self.prev_player(self.to_act);
+        self.players.get_mut(&option_player).unwrap().has_option = true;
+
+        self.request_move();
+    }
+
+    /// Put this street's bets in with the rest of the hand's, then deal the next street
+    fn deal_next_street(&mut self) {
+        self.return_uncalled_bet();
+
+        for player in self.players.values_mut() {
+            if !player.eliminated {
+                player.hand_contrib += player.street_contrib;
+                player.street_contrib = 0;
+                player.has_option = false;
+            }
+        }
+
+        self.current_bet = 0;
+        self.min_raise = self.big_blind;
+        self.last_aggressor = None;
+
+        self.street = match self.street {
+            Street::PreFlop => Street::Flop,
+            Street::Flop    => Street::Turn,
+            Street::Turn    => Street::River,
+            Street::River   => Street::River, // Nothing comes after the river
+        };
+        self.deal_street();
+    }
+
+    fn deal_street(&mut self) {
+        // Deal the cards for the street
+        let revealed_cards_raw : Vec<Card>;
+        let street_name;
+        match self.street {
+            Street::Flop => {
+                log!("GAME - Flop: {:?}",&self.board[0..3]);
+                revealed_cards_raw = self.board[0..3].to_vec();
+                street_name = "Flop";
+            },
+            Street::Turn => {
+                log!("GAME - Turn: {:?}",&self.board[3]);
+                revealed_cards_raw = vec!(self.board[3]);
+                street_name = "Turn";
+            },
+            Street::River => {
+                log!("GAME - River: {:?}",&self.board[4]);
+                revealed_cards_raw = vec!(self.board[4]);
+                street_name = "River";
+            }
+            _ => {
+                panic!("Invalid street!");
+            }
+        }
+
+        let revealed_cards = revealed_cards_raw.iter()
+                                               .map(card_to_string)
+                                               .collect::<Vec<_>>();
+
+        let street_info = StreetInfo {
+            info : "StreetInfo".to_string(),
+            street : street_name.to_string(),
+            button_player : self.seat_order[self.button],
+            board_cards_revealed : revealed_cards,
+            hand_number : self.hand_number,
+        };
+
+        self.send_to_all_players(GameEvent::Street(street_info));
+    }
+
+    fn end_hand(&mut self) {
+        self.return_uncalled_bet();
+
+        // Figure out winners, sidepots, etc
+        // Eliminate players
+        for player in self.players.values_mut() {
+            player.hand_contrib += player.street_contrib;
+            player.street_contrib = 0;
+        }
+
+        let mut to_pay = Vec::new();
+        let mut must_show = Vec::new();
+
+        for _ in 0..self.num_players {
+            to_pay.push(0);
+            must_show.push(false);
+        }
+
+        // If everyone else folded, the last player takes every pot without showing
+        let in_hand = self.players.iter()
+                                  .filter(|&(_, player)| !player.folded)
+                                  .map(|(&id, _)| id)
+                                  .collect::<Vec<_>>();
+        let won_by_fold = in_hand.len() == 1;
+
+        let mut pot_infos = Vec::new();
+        for pot in self.pots() {
+            if won_by_fold {
+                log!("DEBUG - UNCONTESTED: {} to {}",pot.size,in_hand[0]);
+                to_pay[in_hand[0]] += pot.size;
+                pot_infos.push(PotInfo {
+                    size : pot.size,
+                    eligible : pot.eligible,
+                    winners : vec![in_hand[0]],
+                    hand : None,
+                    shares : vec![(in_hand[0], pot.size)],
+                });
+                continue;
+            }
+
+            log!("DEBUG - players in pot {:?}",pot.eligible);
+
+            let eligible = pot.eligible.clone();
+            let contested = pot.eligible.len() > 1;
+            let winners = self.get_winners(pot.eligible);
+
+            log!("DEBUG - WINNERS: {:?}",winners);
+
+            // Nobody wins a contested pot without showing
+            if contested {
+                for &id in &winners {
+                    must_show[id] = true;
+                }
+            }
+
+            // Split payout between winners
+            let payout = pot.size;
+            let indiv_payout = payout / winners.len();
+
+            let mut shares = winners.iter()
+                                    .map(|&id| (id, indiv_payout))
+                                    .collect::<Vec<_>>();
+
+            let mut paid_out = indiv_payout * winners.len();
+
+            // Any leftover change goes to the left of the button
+            let mut change_target = 1;
+            while paid_out < payout {
+                if let Some(share) = shares.iter_mut().find(|share| share.0 == self.seat_order[change_target]) {
+                    share.1 += 1;
+                    paid_out += 1;
+                }
+                change_target += 1;
+            }
+
+            for &(id, share) in &shares {
+                to_pay[id] += share;
+            }
+
+            // A pot nobody contested was never shown down for
+            let hand = if contested {
+                Some(rank_name(&self.players[&winners[0]].get_rank(&self.board)))
+            } else {
+                None
+            };
+
+            pot_infos.push(PotInfo {
+                size : payout,
+                eligible,
+                winners,
+                hand,
+                shares,
+            });
+        }
+
+        for player in self.players.values_mut() {
+            player.hand_contrib = 0;
+            player.ante_contrib = 0;
+        }
+
+        let payouts = (0..self.num_players).map(|x| (x, to_pay[x]))
+                                           .collect::<Vec<(usize, usize)>>();
+        
+        // Players show in turn. After the first, a player only shows a hand that beats or
+        // ties the best one shown so far, or that wins a pot, unless they always show.
+        let showdown_order = if won_by_fold { Vec::new() } else { self.showdown_order() };
+        let mut best_shown : Option<Rank> = None;
+        let mut hands_revealed = Vec::new();
+        for &id in &showdown_order {
+            let player = &self.players[&id];
+            let rank = player.get_rank(&self.board);
+            let good_enough = match best_shown {
+                Some(ref best) => rank >= *best,
+                None => true,
+            };
+
+            if good_enough || must_show[id] || player.always_show {
+                hands_revealed.push((id, (card_to_string(&player.hole_cards[0]),
+                                          card_to_string(&player.hole_cards[1]))));
+                if good_enough {
+                    best_shown = Some(rank);
+                }
+            } else {
+                log!("GAME - Player {} mucks",player.display_name);
+            }
+        }
+
+        let payout_info = PayoutInfo {
+            info : "PayoutInfo".to_string(),
+            reason : if won_by_fold { "All others folded" } else { "Showdown" }.to_string(),
+            payouts,
+            pots : pot_infos,
+            showdown_order,
+            hole_cards : hands_revealed,
+        };
+
+        self.send_to_all_players(GameEvent::Payout(payout_info));
+
+        // Print summary of payouts
+        log!("BOARD - {:?}",self.board);
+        log!("HAND PAYOUTS");
+        for (&id, player) in &mut self.players {
+            if player.folded {
+                log!("{}:{} folded",id, player.display_name);
+            } else if won_by_fold {
+                log!("{}:{} - {} uncontested",id, player.display_name, to_pay[id]);
+            } else {
+                log!("{}:{} - {} for {:?} ({:?})",id, player.display_name, to_pay[id], 
+                                            player.hole_cards, player.get_rank(&self.board));
+            }
+            player.chips += to_pay[id];
+
+            if player.chips == 0 && !player.eliminated {
+                player.eliminated = true;
+                player.folded = true;
+                log!("{} eliminated!",player.display_name);
+                
+                // TODO: Move this somewhere out of mutable borrow of game!!
+                //let player_eliminated_info = PlayerEliminatedInfo {
+                //    info : "PlayerEliminatedInfo".to_string(),
+                //    eliminated_player : id,
+                //};
+                //self.send_to_all_players(GameEvent::PlayerEliminated(player_eliminated_info));
+            }
+        }
+
+        // If the game's over, for now just set the internal variable to true
+        self.game_over = self.players.iter()
+                                     .fold(0, |sum, (_, player)| 
+                                        if player.eliminated { sum + 1 } else { sum }
+                                    ) == self.num_players - 1;
+
+        if self.game_over {
+            let mut winning_player = self.num_players;
+
+            for (&id, player) in self.players.iter() {
+                if player.chips > 0 {
+                    winning_player = id;
+                    break;
+                }
+            }
+
+            let game_over_info = GameOverInfo {
+                info : "GameOverInfo".to_string(),
+                winning_player,
+                seed : self.seed,
+            };
+            self.send_to_all_players(GameEvent::GameOver(game_over_info));
+        } else {
+            self.new_hand();
+        }
+    }
+
+    /// Sets up a new hand: shuffles a new deck, deals, etc.
+    fn new_hand(&mut self) {
+        // Increase hand number
+        self.hand_number += 1;
+
+        // Create a new deck
+        self.deck = create_deck(&mut self.rng);
+
+        // Move up the blinds if the level is over
+        if self.is_level_over() {
+            self.next_blind_level();
+        }
+
+        // Top up time banks
+        if self.bank_refill_hands > 0 && self.hand_number > 1
+           && (self.hand_number - 1).is_multiple_of(self.bank_refill_hands) {
+            let full_bank = Duration::from_secs(self.time_bank);
+            let refill = Duration::from_secs(self.bank_refill);
+            for player in self.players.values_mut() {
+                player.time_bank = full_bank.min(player.time_bank + refill);
+            }
+        }
+
+        // Reset min-raise
+        self.min_raise = self.big_blind;
+
+        // Move the button
+        let (small_blind_player, big_blind_player) = self.move_button();
+
+        // Deal the hand
+        self.board = deal_community(&mut self.deck);
+        for plyr in self.players.values_mut() {
+            let cards = deal_hole(&mut self.deck);
+            plyr.give_hand(&cards);
+        }
+
+        #[cfg(test)]
+        {
+            self.deal_stacked();
+        }
+
+        for (&id, player) in self.players.iter() {
+            let hole_card_info = HoleCardInfo {
+                info : "HoleCardInfo".to_string(),
+                hole_cards : (card_to_string(&player.hole_cards[0]),
+                              card_to_string(&player.hole_cards[1])),
+                hand_number : self.hand_number,
+            };
+            self.send_to_player(id, GameEvent::HoleCards(hole_card_info));
+        }
+
+        // Reset some player stuff and print chip counts
+        log!("CHIP COUNTS");
+        for (id, player) in &mut self.players {
+            log!("{}:{} - {}", id, player.display_name, player.chips);
+
+            if !player.eliminated {
+                player.folded = false;
+                player.all_in = false;
+            }
+        }
+
+        // Reset some game stuff
+        self.num_in_play = self.num_players - self.num_eliminated;
+        self.num_folded = 0;
+
+        self.street = Street::PreFlop;
+
+        // Take antes before the blinds
+        if self.ante > 0 {
+            self.post_antes(big_blind_player);
+        }
+
+        // Post the blinds. Either blind may already be all-in from the ante, so
+        // we say who's posting rather than going round the table.
+        self.players.get_mut(&big_blind_player).unwrap().has_option = true;
+        let (small_blind, big_blind) = (self.small_blind, self.big_blind);
+        if let Some(small_blind_player) = small_blind_player {
+            self.to_act = small_blind_player;
+            self.take_action(Action::PostBlind(small_blind), false);
+        }
+        self.to_act = big_blind_player;
+        self.take_action(Action::PostBlind(big_blind), false);
+
+        self.current_bet = big_blind;
+
+        let street_info = StreetInfo {
+            info : "StreetInfo".to_string(),
+            street : "PreFlop".to_string(),
+            button_player : self.seat_order[self.button],
+            board_cards_revealed : Vec::<String>::new(),
+            hand_number : self.hand_number,
+        };
+        self.send_to_all_players(GameEvent::Street(street_info));
+
+        // Start action, unless the blinds and antes have left nobody to bet against
+        if self.is_hand_over() {
+            self.end_hand();
+        } else if self.is_betting_over() {
+            self.run_out();
+        } else {
+            self.to_act = self.next_player(big_blind_player);
+            self.request_move();
+        }
+
+    } // pub fn new_hand
+
+    /// Move the button and blinds on for a new hand, turning the seat order so the button is
+    /// first. Returns who posts the small blind, if anyone, and who posts the big blind.
+    ///
+    /// The big blind moves on to the next player still in the game, so nobody misses it or
+    /// posts it twice in a row. The small blind is due from last hand's big blind and the button
+    /// goes to last hand's small blind, even if they've been knocked out since: then nobody
+    /// posts the small blind, or the button sits in front of an empty seat. Heads up, the button
+    /// posts the small blind instead.
+    fn move_button(&mut self) -> (Option<usize>, usize) {
+        let heads_up = self.num_in_play() == 2;
+
+        let (button, small_blind_seat, big_blind_seat);
+        if self.hand_number == 1 {
+            button = self.seat_order[1];
+            small_blind_seat = if heads_up { button } else { self.next_live_seat(button) };
+            big_blind_seat = self.next_live_seat(small_blind_seat);
+        } else {
+            big_blind_seat = self.next_live_seat(self.big_blind_seat);
+            if heads_up {
+                button = self.next_live_seat(big_blind_seat);
+                small_blind_seat = button;
+            } else {
+                button = self.small_blind_seat;
+                small_blind_seat = self.big_blind_seat;
+            }
+        }
+
+        let position = self.seat_order.iter().position(|&id| id == button).unwrap();
+        self.seat_order.rotate_left(position);
+
+        self.small_blind_seat = small_blind_seat;
+        self.big_blind_seat = big_blind_seat;
+
+        if self.players[&small_blind_seat].eliminated {
+            log!("GAME - Dead small blind");
+            (None, big_blind_seat)
+        } else {
+            (Some(small_blind_seat), big_blind_seat)
+        }
+    }
+
+    /// The next player clockwise from `seat` who's still in the game, whatever they did last hand
+    fn next_live_seat(&self, seat : usize) -> usize {
+        let position = self.seat_order.iter().position(|&id| id == seat).unwrap();
+
+        (1..self.seat_order.len() + 1).map(|offset| self.seat_order[(position + offset) % self.seat_order.len()])
+                                      .find(|id| !self.players[id].eliminated)
+                                      .unwrap()
+    }
+
+    /// Deal the next hand from a stacked deck instead of a shuffled one. `hole_cards[seat]`
+    /// goes to whoever is in that seat once the button has moved, so seat 0 is the button.
+    #[cfg(test)]
+    pub fn stack_deck(&mut self, hole_cards : Vec<Vec<Card>>, board : Vec<Card>) {
+        assert_eq!(board.len(), 5);
+        assert!(hole_cards.iter().all(|cards| cards.len() == 2));
+
+        self.stacked_deck = Some((hole_cards, board));
+    }
+
+    /// Give a player a stack of `chips`, counting whatever they've put in this hand,
+    /// and change the chips in play to match
+    #[cfg(test)]
+    pub fn set_stack(&mut self, player_id : usize, chips : usize) {
+        let player = self.players.get_mut(&player_id).unwrap();
+        let in_front = player.chips + player.hand_contrib + player.street_contrib;
+
+        player.chips = chips - player.hand_contrib - player.street_contrib;
+        self.total_chips = self.total_chips + chips - in_front;
+    }
+
+    #[cfg(test)]
+    fn deal_stacked(&mut self) {
+        if let Some((hole_cards, board)) = self.stacked_deck.take() {
+            assert_eq!(hole_cards.len(), self.seat_order.len());
+
+            self.board = board;
+            for (seat, &id) in self.seat_order.iter().enumerate() {
+                self.players.get_mut(&id).unwrap().give_hand(&hole_cards[seat]);
+            }
+        }
+    }
+
+    /// Tell everyone who we're waiting on and start their clock
+    fn request_move(&mut self) {
+        self.move_started = Some(Instant::now());
+
+        let to_move = ToMoveInfo {
+            info : "ToMoveInfo".to_string(),
+            player_id : self.to_act,
+            hand_number : self.hand_number,
+            move_time : self.move_time,
+            time_bank : duration_to_secs(self.players[&self.to_act].time_bank),
+            legal_actions : None,
+        };
+
+        // Only the player to act is told what they can do
+        let mut to_move_acting = to_move.clone();
+        to_move_acting.legal_actions = self.legal_actions().ok();
+
+        self.sink.public_event(&GameEvent::ToMove(to_move.clone()));
+        for (&id, _) in self.players
+                                .iter()
+                                .filter(|&(_, player)| !player.eliminated ) {
+            if id == self.to_act {
+                self.send_to_player(id, GameEvent::ToMove(to_move_acting.clone()));
+            } else {
+                self.send_to_player(id, GameEvent::ToMove(to_move.clone()));
+            }
+        }
+    }
+
+    /// What the player to act can do.
+    /// These follow the same rules `player_action` uses to interpret moves.
+    pub fn legal_actions(&self) -> Result<LegalActions, GameError> {
+        if !self.started {
+            return Err(GameError::NotStarted);
+        }
+
+        if self.game_over {
+            return Err(GameError::GameOver);
+        }
+
+        let plyr = &self.players[&self.to_act];
+        let to_call = self.current_bet.saturating_sub(plyr.street_contrib);
+        let all_in_to = plyr.street_contrib + plyr.chips;
+
+        // Players can only raise if they have more than it takes to call, and someone
+        // else has chips to call the raise with
+        let someone_to_raise = self.players.iter()
+                                           .any(|(&id, player)| id != self.to_act && !player.folded
+                                                                && !player.eliminated && !player.all_in);
+        let (min_raise_to, max_raise_to) = if plyr.chips > to_call && someone_to_raise {
+            (Some(all_in_to.min(self.current_bet + self.min_raise)), Some(all_in_to))
+        } else {
+            (None, None)
+        };
+
+        Ok(LegalActions {
+            player_id : self.to_act,
+            can_check : to_call == 0,
+            call_amount : plyr.chips.min(to_call),
+            min_raise_to,
+            max_raise_to,
+        })
+    }
+
+    /// Take the ante from everyone still in the game, or just from the big blind
+    /// if we're playing a big blind ante
+    fn post_antes(&mut self, big_blind_player : usize) {
+        let ante = self.ante;
+
+        if self.big_blind_ante {
+            self.to_act = big_blind_player;
+            self.take_action(Action::PostAnte(ante), false);
+        } else {
+            for id in self.seat_order.clone() {
+                if !self.players[&id].eliminated {
+                    self.to_act = id;
+                    self.take_action(Action::PostAnte(ante), false);
+                }
+            }
+        }
+    }
+
+    /// Has the current blind level run its course?
+    fn is_level_over(&self) -> bool {
+        if self.blind_schedule.is_empty() {
+            return false;
+        }
+
+        // Replays go up when the recorded game did, however long they take
+        if let Some(ref level_ups) = self.replay_level_ups {
+            return level_ups.contains(&self.hand_number);
+        }
+
+        let hands_over = self.level_hands > 0
+                         && self.hand_number - self.level_start_hand >= self.level_hands;
+        let time_over = self.level_minutes > 0
+                        && self.level_start_time.elapsed() >= Duration::from_secs(self.level_minutes * 60);
+
+        hands_over || time_over
+    }
+
+    /// Move on to the next level of the blind schedule and tell everyone about it
+    fn next_blind_level(&mut self) {
+        let level = self.blind_schedule.remove(0);
+
+        self.small_blind = level.small_blind;
+        self.big_blind = level.big_blind;
+        self.ante = level.ante;
+        self.blind_level += 1;
+        self.level_start_hand = self.hand_number;
+        self.level_start_time = Instant::now();
+
+        if let Some(ref mut record) = self.record {
+            record.level_ups.push(self.hand_number);
+        }
+
+        log!("GAME - Blinds are now {}/{} ante {}",self.small_blind,self.big_blind,self.ante);
+
+        let blind_level_info = BlindLevelInfo {
+            info : "BlindLevelInfo".to_string(),
+            level : self.blind_level,
+            small_blind : self.small_blind,
+            big_blind : self.big_blind,
+            ante : self.ante,
+            hand_number : self.hand_number,
+        };
+        self.send_to_all_players(GameEvent::BlindLevel(blind_level_info));
+    }
+
+    /// Call this function to indicate the players are in and the game is ready to start.
+    pub fn start(&mut self) -> Result<(), GameError> {
+        if self.started {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        if self.num_players < 2 {
+            return Err(GameError::NotEnoughPlayers);
+        }
+
+        // Seats are drawn once everyone's in
+        self.rng.shuffle(&mut self.seat_order);
+
+        self.record = Some(GameRecord {
+            seed : self.seed,
+            players : (0..self.num_players).map(|id| self.players[&id].display_name.clone()).collect(),
+            starting_stack : self.starting_stack,
+            small_blind : self.small_blind,
+            big_blind : self.big_blind,
+            ante : self.ante,
+            big_blind_ante : self.big_blind_ante,
+            blind_schedule : self.blind_schedule.clone(),
+            move_time : self.move_time,
+            time_bank : self.time_bank,
+            bank_refill : self.bank_refill,
+            bank_refill_hands : self.bank_refill_hands,
+            always_show : self.players.iter()
+                                      .filter(|&(_, player)| player.always_show)
+                                      .map(|(&id, _)| id)
+                                      .collect(),
+            level_ups : Vec::new(),
+            moves : Vec::new(),
+        });
+
+        log!("DEBUG - Sending player information");
+        
+        let game_info = GameTableInfo {
+            info : "GameTableInfo".to_string(),
+            starting_stack : self.starting_stack,
+            seat_order : self.seat_order.clone(), // TODO: differnet soln
+            button_player : self.seat_order[self.button],
+            display_names : self.seat_order.iter()
+                                           .map(|&id| (id, self.players[&id].display_name.clone()))
+                                           .collect::<Vec<_>>(),
+            small_blind : self.small_blind,
+            big_blind : self.big_blind,
+            ante : self.ante,
+            big_blind_ante : self.big_blind_ante,
+            seed_hash : seed_commitment(self.seed),
+        };
+
+        for (&id, player) in self.players.iter() {
+            let player_info = PlayerPrivateInfo {
+                info : "PlayerPrivateInfo".to_string(),
+                ingame_id : id,
+                secret_id : player.secret_id.simple().to_string(),
+            };
+
+            log!("DEBUG - Sending info to Player {}",player.display_name);
+
+            self.send_to_player(id, GameEvent::PlayerPrivate(player_info));
+        }
+
+        self.send_to_all_players(GameEvent::GameTable(game_info));
+        
+        for player in self.players.values_mut() {
+            player.time_bank = Duration::from_secs(self.time_bank);
+        }
+
+        log!("GAME - Starting");
+        self.total_chips = self.players.values().map(|player| player.chips).sum();
+        self.started = true;
+        self.level_start_time = Instant::now();
+        self.next_street();
+        self.assert_invariants();
+
+        Ok(())
+    }
+
+    fn num_in_play(&self) -> usize {
+        self.players.iter().fold(0,|sum, (_, player)| if !player.eliminated {sum + 1} else {sum})
+    }
+
+    /// Return the index of the next unfolded player in the move order
+    fn next_player(&self, current_player: usize) -> usize {
+        let mut found_current = false;
+        
+        for &i in &self.seat_order {
+            if i == current_player {
+                found_current = true;
+            } else if found_current {
+                let plyr = self.players.get(&i).unwrap();
+                if !plyr.folded && !plyr.all_in && !plyr.eliminated {
+                    return i;
+                }
+            }
+        }
+
+        // We got to the end and didn't find anyone... So return the first unfolded player
+        for &i in &self.seat_order {
+            if i == current_player {
+                //panic!("Only one unfolded player!");
+                // We only have one player left
+                return i;
+            } else {
+                let plyr = self.players.get(&i).unwrap();
+                if !plyr.folded && !plyr.all_in && !plyr.eliminated {
+                    return i;
+                }
+            }
+        }
+
+        panic!("Something wrong in Game::next_player");
+    }
+
+        /// Return the index of the next unfolded player in the move order
+    fn prev_player(&self, current_player: usize) -> usize {
+        let mut found_current = false;
+
+        let mut reverse_seat = self.seat_order.clone();
+        reverse_seat.reverse();
+        
+        for &i in &reverse_seat {
+            if i == current_player {
+                found_current = true;
+            } else if found_current {
+                let plyr = self.players.get(&i).unwrap();
+                if !plyr.folded && !plyr.all_in && !plyr.eliminated {
+                    return i;
+                }
+            }
+        }
+
+        // We got to the end and didn't find anyone... So return the first unfolded player
+        for &i in &reverse_seat {
+            if i == current_player {
+                //panic!("Only one unfolded player!");
+                // We only have one player left
+                return i;
+            } else {
+                let plyr = self.players.get(&i).unwrap();
+                if !plyr.folded && !plyr.all_in && !plyr.eliminated {
+                    return i;
+                }
+            }
+        }
+
+        panic!("Something wrong in Game::next_player");
+    }
+
+    /// Tell all players (and anyone else watching) about an event
+    fn send_to_all_players(&self, event : GameEvent) {
+        self.sink.public_event(&event);
+
+        // Don't bother eliminated players
+        for (&id, _) in self.players
+                                .iter()
+                                .filter(|&(_, player)| !player.eliminated ) {
+            self.sink.player_event(id, &event);
+        }
+    }
+
+    /// Tell a single player about an event
+    fn send_to_player(&self, player_id: usize, event: GameEvent) {
+        self.sink.player_event(player_id, &event);
+    }
+
+    /// Split the chips put in this hand into the main pot and any side pots.
+    /// Anything folded players put in above what anyone still in the hand has is dead money.
+    /// Antes are layered like bets, so a player who couldn't cover theirs only wins as much
+    /// of each ante as they put in. A big blind ante is posted for the whole table, so that's
+    /// dead money in the main pot.
+    pub fn pots(&self) -> Vec<Pot> {
+        let big_blind_ante = self.big_blind_ante;
+        let contrib = |player : &Player| {
+            let bets = player.hand_contrib + player.street_contrib;
+            if big_blind_ante { bets - player.ante_contrib } else { bets }
+        };
+
+        let dead_money = if big_blind_ante {
+            self.players.iter().fold(0, |sum, (_, player)| sum + player.ante_contrib)
+        } else {
+            0
+        };
+
+        // Each different amount put in by a player still in the hand caps a pot
+        let mut pot_levels = self.players.iter()
+                                         .filter(|&(_, player)| !player.folded && !player.eliminated)
+                                         .map(|(_, player)| contrib(player))
+                                         .collect::<Vec<_>>();
+        pot_levels.sort();
+        pot_levels.dedup();
+
+        let mut pots = Vec::new();
+        let mut prev_level = 0;
+        for (pot_number, &level) in pot_levels.iter().enumerate() {
+            let mut size = if pot_number == 0 { dead_money } else { 0 };
+            let mut eligible = Vec::new();
+            for (&id, player) in self.players.iter() {
+                if !player.folded && !player.eliminated && contrib(player) >= level {
+                    eligible.push(id);
+                }
+
+                size += contrib(player).min(level) - contrib(player).min(prev_level);
+            }
+            prev_level = level;
+
+            if pot_number == pot_levels.len() - 1 {
+                size += self.players.iter()
+                                    .fold(0, |sum, (_, player)| sum + contrib(player).saturating_sub(level));
+            }
+
+            if size > 0 {
+                eligible.sort();
+                pots.push(Pot {
+                    size,
+                    eligible,
+                });
+            }
+        }
+
+        pots
+    }
+
+    /// Community cards everyone can see right now
+    fn visible_board(&self) -> Vec<Card> {
+        if !self.started {
+            return Vec::new();
+        }
+
+        let num_visible = match self.street {
+            Street::PreFlop => 0,
+            Street::Flop    => 3,
+            Street::Turn    => 4,
+            Street::River   => 5,
+        };
+
+        self.board[0..num_visible].to_vec()
+    }
+
+    /// Everything about the game that isn't secret
+    pub fn public_state(&self) -> GameState {
+        GameState {
+            started : self.started,
+            game_over : self.game_over,
+            hand_number : self.hand_number,
+            street : format!("{:?}",self.street),
+            board : self.visible_board().iter().map(card_to_string).collect(),
+            button_player : if self.started { Some(self.seat_order[self.button]) } else { None },
+            to_act : if self.move_started.is_some() { Some(self.to_act) } else { None },
+            current_bet : self.current_bet,
+            small_blind : self.small_blind,
+            big_blind : self.big_blind,
+            ante : self.ante,
+            pots : self.pots(),
+            players : self.seat_order.iter()
+                                     .map(|&id| {
+                                         let player = &self.players[&id];
+                                         PlayerState {
+                                             player_id : id,
+                                             display_name : player.display_name.clone(),
+                                             chips : player.chips,
+                                             street_contrib : player.street_contrib,
+                                             folded : player.folded,
+                                             all_in : player.all_in,
+                                             eliminated : player.eliminated,
+                                         }
+                                     })
+                                     .collect(),
+        }
+    }
+
+    /// A player's hole cards, if they've been dealt any
+    pub fn hole_cards(&self, player_id : usize) -> Vec<String> {
+        self.players[&player_id].hole_cards.iter().map(card_to_string).collect()
+    }
+
+    /// Find the player with the given secret ID. This works whether or not it's their turn,
+    /// and before the game has started.
+    pub fn authenticate(&self, secret_id : &Uuid) -> Result<usize, GameError> {
+        self.players.iter()
+                    .find(|&(_, player)| player.secret_id == *secret_id)
+                    .map(|(&id, _)| id)
+                    .ok_or(GameError::UnknownSecret)
+    }
+
+    /// At the end of a betting round, give back whatever the biggest bet was over what
+    /// anyone else put in, so it never goes in a pot
+    fn return_uncalled_bet(&mut self) {
+        let (bettor, biggest) = match self.players.iter().max_by_key(|&(_, player)| player.street_contrib) {
+            Some((&id, player)) => (id, player.street_contrib),
+            None => return,
+        };
+
+        let called = self.players.iter()
+                                 .filter(|&(&id, _)| id != bettor)
+                                 .map(|(_, player)| player.street_contrib)
+                                 .max()
+                                 .unwrap_or(0);
+
+        if biggest <= called {
+            return;
+        }
+
+        let uncalled = biggest - called;
+        {
+            let player = self.players.get_mut(&bettor).unwrap();
+            player.street_contrib -= uncalled;
+            player.chips += uncalled;
+            log!("GAME - Uncalled bet of {} returned to Player {}",uncalled,player.display_name);
+        }
+
+        let uncalled_bet_info = UncalledBetInfo {
+            info : "UncalledBetInfo".to_string(),
+            player_id : bettor,
+            amount : uncalled,
+            hand_number : self.hand_number,
+        };
+        self.send_to_all_players(GameEvent::UncalledBet(uncalled_bet_info));
+    }
+
+    /// The order players still in the hand show down in: whoever bet or raised last on the
+    /// river shows first, or if nobody did, the first player left of the button. Everyone
+    /// else follows clockwise.
+    fn showdown_order(&self) -> Vec<usize> {
+        let mut order = self.seat_order[1..].iter()
+                                            .chain(self.seat_order[..1].iter())
+                                            .cloned()
+                                            .filter(|id| !self.players[id].folded)
+                                            .collect::<Vec<_>>();
+
+        if self.street == Street::River {
+            if let Some(aggressor) = self.last_aggressor {
+                if let Some(seat) = order.iter().position(|&id| id == aggressor) {
+                    order.rotate_left(seat);
+                }
+            }
+        }
+
+        order
+    }
+
+    /// Of the players still in the hand, return a `Vec<usize>` of 
+    /// the ID(s) of the player(s) with the best hand
+    fn get_winners(&self, ids: Vec<usize>) -> Vec<usize> {
+        let mut best_hands = Vec::<usize>::new();
+
+        let best_rank = ids.iter()
+                           .fold(Rank::HighCard(0), |best, id| {
+                                let player = self.players.get(id).unwrap();
+                                if !player.folded && !player.eliminated {
+                                    let new_rank = player.get_rank(&self.board);
+                                    if new_rank > best {
+                                        new_rank.to_owned()
+                                    } else {
+                                        best
+                                    }
+                                } else {
+                                    best
+                                }
+                           });
+
+        for id in ids {
+            let player = &self.players[&id];
+            if !player.folded && !player.eliminated && player.get_rank(&self.board) == best_rank {
+                best_hands.push(id);
+            }
+        }
+
+        best_hands
+    } // pub fn get_winners
+} // impl Game
+
+/// Returns a vec of 2 cards to be used as a player's hole cards
+fn deal_hole(deck: &mut Vec<Card>) -> Vec<Card> {
+    deal_cards(deck, 2)
+}
+
+/// Returns a vec of 5 cards as community cards
+fn deal_community(deck: &mut Vec<Card>) -> Vec<Card> {
+    deal_cards(deck, 5)
+}
+
+/// Returns a vec with capacity `num` filled with cards
+fn deal_cards(deck: &mut Vec<Card>, num: usize) -> Vec<Card> {
+    let mut cards = Vec::<Card>::with_capacity(num);
+
+    for _ in 0..num {
+        cards.push(deck.pop().unwrap());
+    }
+
+    cards
+}
+
+/// Returns a shuffled and dealable deck. The same rng state always gives the same deck.
+fn create_deck(rng: &mut ChaChaRng) -> Vec<Card> {
+    let mut deck = Vec::with_capacity(52);
+    for &value in Value::values().iter() {
+        for &suit in Suit::suits().iter() {
+            deck.push(Card {
+                value,
+                suit,
+            });
+        }
+    }
+
+    rng.shuffle(&mut deck);
+    deck
+}
+
+fn seeded_rng(seed: u64) -> ChaChaRng {
+    ChaChaRng::from_seed(&[seed as u32, (seed >> 32) as u32])
+}
+
+/// SHA-256 of the seed's little-endian bytes, in hex. Players can check it against the seed
+/// they're told at the end of the game, to see the cards weren't changed along the way.
+pub fn seed_commitment(seed: u64) -> String {
+    let bytes = (0..8).map(|i| (seed >> (8 * i)) as u8).collect::<Vec<u8>>();
+
+    Sha256::digest(&bytes).iter()
+                          .map(|byte| format!("{:02x}", byte))
+                          .collect()
+}
+
+/// Duration as a (fractional) number of seconds
+fn duration_to_secs(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + duration.subsec_nanos() as f64 / 1e9
+}
+
+fn card_to_string(card: &Card) -> String {
+    format!("{}{}",card.value.to_char(),card.suit.to_char())
+}
+
+/// The kind of hand a rank is, e.g. "Full House"
+fn rank_name(rank: &Rank) -> String {
+    match *rank {
+        Rank::HighCard(_)      => "High Card",
+        Rank::OnePair(_)       => "One Pair",
+        Rank::TwoPair(_)       => "Two Pair",
+        Rank::ThreeOfAKind(_)  => "Three of a Kind",
+        Rank::Straight(_)      => "Straight",
+        Rank::Flush(_)         => "Flush",
+        Rank::FullHouse(_)     => "Full House",
+        Rank::FourOfAKind(_)   => "Four of a Kind",
+        Rank::StraightFlush(_) => "Straight Flush",
+    }.to_string()
+}
\ No newline at end of file
@@ -0,0 +1,46 @@
+//! The Texas Hold'em rules engine behind the Gateau server. There's no networking in here:
+//! a `Game` tells players what happens through a `NotificationSink`, which can deliver
+//! events however it likes.
+
+extern crate rs_poker;
+extern crate rand;
+extern crate uuid;
+#[macro_use] extern crate serde_derive;
+extern crate serde;
+extern crate sha2;
+#[cfg(test)] #[macro_use] extern crate proptest;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET : AtomicBool = AtomicBool::new(false);
+
+/// Turn the engine's logging on or off. It's on unless turned off, but simulations
+/// playing thousands of hands are better off without it.
+pub fn set_logging(on : bool) {
+    QUIET.store(!on, Ordering::Relaxed);
+}
+
+#[doc(hidden)]
+pub fn logging() -> bool {
+    !QUIET.load(Ordering::Relaxed)
+}
+
+/// `println!`, unless logging is turned off. The server logs through this too, so one
+/// switch covers everything.
+#[macro_export]
+macro_rules! log {
+    ($($arg:tt)*) => {
+        if $crate::logging() {
+            println!($($arg)*);
+        }
+    }
+}
+
+pub mod game;
+pub mod player;
+pub mod events;
+pub mod error;
+pub mod bot;
+
+#[cfg(test)]
+mod tests;
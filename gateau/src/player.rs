@@ -2,6 +2,7 @@ extern crate rs_poker;
 
 use rs_poker::core::{Card, Hand, Rank, Rankable};
 use uuid::Uuid;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct Player {
@@ -9,44 +10,49 @@ pub struct Player {
     pub folded : bool,
     pub chips : usize,
     pub display_name : String,
-    pub address : String,
     pub street_contrib : usize,
     pub hand_contrib : usize,
+    pub ante_contrib : usize,
     pub has_option : bool,
     pub all_in : bool,
     pub eliminated : bool,
+    pub always_show : bool,
     pub secret_id : Uuid,
+    pub time_bank : Duration,
 }
 
 impl Player {
-    pub fn new(name : String, address: String, starting_stack : usize) -> Player {
+    pub fn new(name : String, starting_stack : usize) -> Player {
         Player {
             chips : starting_stack,
             display_name : name,
-            address : address,
             hole_cards : Vec::new(),
             folded : false,
             hand_contrib : 0,
+            ante_contrib : 0,
             street_contrib : 0,
             has_option : false,
             all_in : false,
             eliminated : false,
+            always_show : false,
             secret_id : Uuid::new_v4(),
+            time_bank : Duration::from_secs(0),
         }
     }
 
-    pub fn give_hand(&mut self, hand : &Vec<Card>) {
-        self.hole_cards = hand.clone();
-        self.folded = false;
+    pub fn give_hand(&mut self, hand : &[Card]) {
+        self.hole_cards = hand.to_vec();
+        // Busted players are still dealt cards, but they're never in the hand
+        self.folded = self.eliminated;
     }
     
-    pub fn get_rank(&self, community: &Vec<Card>) -> Rank {
+    pub fn get_rank(&self, community: &[Card]) -> Rank {
         let mut my_hand = community.to_owned();
 
         for card in &self.hole_cards {
-            my_hand.push(card.clone());
+            my_hand.push(*card);
         }
 
-        return Hand::new_with_cards(my_hand).rank();
+        Hand::new_with_cards(my_hand).rank()
     }
 }
\ No newline at end of file
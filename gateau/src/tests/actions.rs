@@ -0,0 +1,16 @@
+//! Moves players can and can't make
+
+use super::*;
+use error::GameError;
+
+#[test]
+fn players_cant_post_blinds_or_antes() {
+    let (mut game, _sink) = new_game(3);
+    game.start().unwrap();
+    let to_act = game.to_act;
+
+    // Only the game posts these
+    assert_eq!(game.player_action(to_act, Action::PostBlind(2)), Err(GameError::InvalidAction));
+    assert_eq!(game.player_action(to_act, Action::PostAnte(2)), Err(GameError::InvalidAction));
+    assert_eq!(game.to_act, to_act);
+}
@@ -0,0 +1,63 @@
+use error::GameError;
+use game::{BlindLevel, Game};
+
+fn level(small_blind : usize, big_blind : usize) -> BlindLevel {
+    BlindLevel {
+        small_blind,
+        big_blind,
+        ante : 0,
+    }
+}
+
+#[test]
+fn big_blind_cant_be_zero() {
+    let mut game = Game::new(200);
+
+    game.set_small_blind(0).unwrap();
+    assert_eq!(game.set_big_blind(0), Err(GameError::InvalidConfig));
+}
+
+#[test]
+fn small_blind_cant_be_more_than_big_blind() {
+    let mut game = Game::new(200);
+
+    // The default big blind is 2
+    assert_eq!(game.set_small_blind(5), Err(GameError::InvalidConfig));
+
+    game.set_big_blind(10).unwrap();
+    game.set_small_blind(5).unwrap();
+    assert_eq!(game.set_big_blind(4), Err(GameError::InvalidConfig));
+}
+
+#[test]
+fn every_blind_level_is_checked() {
+    let mut game = Game::new(200);
+
+    assert_eq!(game.set_blind_schedule(vec![level(2, 4), level(2, 0)]), Err(GameError::InvalidConfig));
+    assert_eq!(game.set_blind_schedule(vec![level(5, 4), level(5, 10)]), Err(GameError::InvalidConfig));
+    game.set_blind_schedule(vec![level(2, 4), level(5, 10)]).unwrap();
+}
+
+#[test]
+fn game_needs_two_players_to_start() {
+    let mut game = Game::new(200);
+    assert_eq!(game.start(), Err(GameError::NotEnoughPlayers));
+
+    game.add_player("Player 0").unwrap();
+    assert_eq!(game.start(), Err(GameError::NotEnoughPlayers));
+
+    game.add_player("Player 1").unwrap();
+    assert_eq!(game.start(), Ok(()));
+}
+
+#[test]
+fn starting_stack_applies_to_players_already_seated() {
+    let mut game = Game::new(200);
+    let early = game.add_player("Early").unwrap();
+
+    game.set_starting_stack(40).unwrap();
+    let late = game.add_player("Late").unwrap();
+
+    assert_eq!(game.players[&early].chips, 40);
+    assert_eq!(game.players[&late].chips, 40);
+}
@@ -0,0 +1,438 @@
+use game::Action;
+use super::*;
+
+#[test]
+fn split_pot_is_shared_equally() {
+    let (mut game, sink) = new_game(3);
+    game.stack_deck(vec![cards("2c 3d"), cards("4c 5d"), cards("6c 7d")], cards("Ah Kh Qd Jc Ts"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    // Everyone plays the broadway on the board
+    act(&mut game, 0, Action::Call);
+    act(&mut game, 1, Action::Call);
+    act(&mut game, 2, Action::Check);
+    check_down(&mut game, &sink);
+
+    let payout = &payouts(&sink)[0];
+    for &id in &seats {
+        assert_eq!(won(payout, id), 2);
+    }
+}
+
+#[test]
+fn odd_chip_goes_to_first_winner_left_of_button() {
+    let (mut game, sink) = new_game(3);
+    game.set_ante(1).unwrap();
+    game.stack_deck(vec![cards("Ac Ad"), cards("2c 3d"), cards("4c 5d")], cards("Ah Kh Qd Jc Ts"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    // 3 in antes and 4 in blinds, split two ways
+    act(&mut game, 0, Action::Fold);
+    act(&mut game, 1, Action::Call);
+    act(&mut game, 2, Action::Check);
+    check_down(&mut game, &sink);
+
+    let payout = &payouts(&sink)[0];
+    assert_eq!(won(payout, seats[0]), 0);
+    assert_eq!(won(payout, seats[1]), 4);
+    assert_eq!(won(payout, seats[2]), 3);
+}
+
+#[test]
+fn multi_way_side_pots_go_to_best_eligible_hand() {
+    let (mut game, sink) = new_game(4);
+    game.stack_deck(vec![cards("Ah As"), cards("Kh Ks"), cards("5h 5d"), cards("Qh Qs")],
+                    cards("2c 7d 9h Jc 3s"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    set_stack(&mut game, 0, 50);
+    set_stack(&mut game, 1, 100);
+    set_stack(&mut game, 2, 200);
+    set_stack(&mut game, 3, 200);
+
+    act(&mut game, 3, Action::AllIn);
+    act(&mut game, 0, Action::AllIn);
+    act(&mut game, 1, Action::AllIn);
+    act(&mut game, 2, Action::Call);
+
+    // Main pot of 4 x 50, a side pot of 3 x 50, and a side pot of 2 x 100
+    let payout = &payouts(&sink)[0];
+    assert_eq!(won(payout, seats[0]), 200);
+    assert_eq!(won(payout, seats[1]), 150);
+    assert_eq!(won(payout, seats[2]), 0);
+    assert_eq!(won(payout, seats[3]), 200);
+
+    assert!(game.players[&seats[2]].eliminated);
+}
+
+#[test]
+fn side_pot_can_be_split_while_main_pot_is_not() {
+    let (mut game, sink) = new_game(3);
+    game.stack_deck(vec![cards("Ah As"), cards("Kh Kd"), cards("Ks Kc")], cards("2c 7d 9h Jc 3s"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    set_stack(&mut game, 0, 50);
+
+    act(&mut game, 0, Action::AllIn);
+    act(&mut game, 1, Action::AllIn);
+    act(&mut game, 2, Action::Call);
+
+    let payout = &payouts(&sink)[0];
+    assert_eq!(won(payout, seats[0]), 150);
+    assert_eq!(won(payout, seats[1]), 150);
+    assert_eq!(won(payout, seats[2]), 150);
+}
+
+#[test]
+fn all_in_runout_uses_the_whole_board() {
+    let (mut game, sink) = new_game(3);
+    game.stack_deck(vec![cards("2c 3d"), cards("Ah Ad"), cards("Kh Ks")], cards("2h 7d 9s 3h Kd"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    act(&mut game, 0, Action::Fold);
+    act(&mut game, 1, Action::AllIn);
+    act(&mut game, 2, Action::Call);
+
+    // Aces are ahead until the king on the river
+    let payout = &payouts(&sink)[0];
+    assert_eq!(won(payout, seats[0]), 0);
+    assert_eq!(won(payout, seats[1]), 0);
+    assert_eq!(won(payout, seats[2]), 400);
+
+    assert!(game.players[&seats[1]].eliminated);
+    assert_eq!(game.players[&seats[2]].chips + game.players[&seats[2]].street_contrib
+               + game.players[&seats[2]].hand_contrib, 400);
+}
+
+#[test]
+fn all_in_runout_with_unequal_stacks_deals_the_board_without_asking_anyone() {
+    let (mut game, sink) = new_game(3);
+    game.stack_deck(vec![cards("2c 3d"), cards("Ah Ad"), cards("Kh Ks")], cards("2h 7d 9s 3h Kd"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    set_stack(&mut game, 1, 50);
+
+    act(&mut game, 0, Action::Fold);
+    act(&mut game, 1, Action::AllIn);
+
+    // The big blind covers the all-in, so can only call or fold
+    assert_eq!(game.legal_actions().unwrap().min_raise_to, None);
+
+    let seen = sink.public_events().len();
+    act(&mut game, 2, Action::Call);
+
+    let mut streets = Vec::new();
+    for event in sink.public_events().into_iter().skip(seen) {
+        match event {
+            GameEvent::Street(info) => streets.push(info.street),
+            GameEvent::ToMove(info) => panic!("player {} was asked to act in the runout", info.player_id),
+            GameEvent::Payout(_) => break,
+            _ => (),
+        }
+    }
+    assert_eq!(streets, vec!["Flop", "Turn", "River"]);
+
+    let payout = &payouts(&sink)[0];
+    assert_eq!(won(payout, seats[2]), 100);
+    assert!(game.players[&seats[1]].eliminated);
+}
+
+#[test]
+fn win_by_fold_takes_the_pot_without_showing() {
+    let (mut game, sink) = new_game(3);
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    act(&mut game, 0, Action::Bet(6));
+    act(&mut game, 1, Action::Fold);
+    act(&mut game, 2, Action::Fold);
+
+    // The raise over the big blind is given back before the pot is paid
+    let payout = &payouts(&sink)[0];
+    assert_eq!(payout.reason, "All others folded");
+    assert_eq!(won(payout, seats[0]), 5);
+    assert!(payout.hole_cards.is_empty());
+
+    let returned = uncalled_bets(&sink);
+    assert_eq!((returned[0].player_id, returned[0].amount), (seats[0], 4));
+}
+
+/// Players in a payout's showdown who showed their hand, in the order they showed
+fn shown(payout : &PayoutInfo) -> Vec<usize> {
+    payout.hole_cards.iter().map(|&(id, _)| id).collect()
+}
+
+#[test]
+fn river_aggressor_shows_first_and_losers_muck() {
+    let (mut game, sink) = new_game(3);
+    game.stack_deck(vec![cards("Ah As"), cards("Kh Ks"), cards("2c 3d")], cards("7c 8d 9h Jc 4s"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    act(&mut game, 0, Action::Call);
+    act(&mut game, 1, Action::Call);
+    act(&mut game, 2, Action::Check);
+    for _ in 0..2 {
+        act(&mut game, 1, Action::Check);
+        act(&mut game, 2, Action::Check);
+        act(&mut game, 0, Action::Check);
+    }
+    act(&mut game, 1, Action::Check);
+    act(&mut game, 2, Action::Check);
+    act(&mut game, 0, Action::Bet(10));
+    act(&mut game, 1, Action::Call);
+    act(&mut game, 2, Action::Call);
+
+    let payout = &payouts(&sink)[0];
+    assert_eq!(payout.showdown_order, vec![seats[0], seats[1], seats[2]]);
+    assert_eq!(shown(payout), vec![seats[0]]);
+}
+
+#[test]
+fn without_river_betting_first_player_left_of_button_shows_first() {
+    let (mut game, sink) = new_game(3);
+    game.stack_deck(vec![cards("Qh Qs"), cards("2c 3d"), cards("Kh Ks")], cards("7c 8d 9h Jc 4s"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    act(&mut game, 0, Action::Call);
+    act(&mut game, 1, Action::Call);
+    act(&mut game, 2, Action::Check);
+    check_down(&mut game, &sink);
+
+    // Kings beat the first hand shown, queens don't beat the kings
+    let payout = &payouts(&sink)[0];
+    assert_eq!(payout.showdown_order, vec![seats[1], seats[2], seats[0]]);
+    assert_eq!(shown(payout), vec![seats[1], seats[2]]);
+}
+
+#[test]
+fn side_pot_winner_shows_a_hand_that_lost_the_main_pot() {
+    let (mut game, sink) = new_game(3);
+    game.stack_deck(vec![cards("Kh Ks"), cards("Ah As"), cards("Qh Qs")], cards("2c 7d 9h Jc 3s"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    set_stack(&mut game, 1, 50);
+
+    act(&mut game, 0, Action::AllIn);
+    act(&mut game, 1, Action::AllIn);
+    act(&mut game, 2, Action::Call);
+
+    let payout = &payouts(&sink)[0];
+    assert_eq!(shown(payout), vec![seats[1], seats[0]]);
+}
+
+#[test]
+fn players_who_always_show_never_muck() {
+    let (mut game, sink) = new_game(3);
+    for id in 0..3 {
+        game.set_always_show(id, true).unwrap();
+    }
+    game.stack_deck(vec![cards("Qh Qs"), cards("2c 3d"), cards("Kh Ks")], cards("7c 8d 9h Jc 4s"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    act(&mut game, 0, Action::Call);
+    act(&mut game, 1, Action::Call);
+    act(&mut game, 2, Action::Check);
+    check_down(&mut game, &sink);
+
+    let payout = &payouts(&sink)[0];
+    assert_eq!(shown(payout), vec![seats[1], seats[2], seats[0]]);
+}
+
+#[test]
+fn payout_itemizes_each_pot() {
+    let (mut game, sink) = new_game(4);
+    game.stack_deck(vec![cards("Ah As"), cards("Kh Ks"), cards("5h 5d"), cards("Qh Qs")],
+                    cards("2c 7d 9h Jc 3s"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    set_stack(&mut game, 0, 50);
+    set_stack(&mut game, 1, 100);
+    set_stack(&mut game, 2, 200);
+    set_stack(&mut game, 3, 200);
+
+    act(&mut game, 3, Action::AllIn);
+    act(&mut game, 0, Action::AllIn);
+    act(&mut game, 1, Action::AllIn);
+    act(&mut game, 2, Action::Call);
+
+    let payout = &payouts(&sink)[0];
+    let pots = &payout.pots;
+    assert_eq!(pots.len(), 3);
+
+    let mut all = seats.clone();
+    all.sort();
+    assert_eq!((pots[0].size, &pots[0].eligible, &pots[0].winners), (200, &all, &vec![seats[0]]));
+    assert_eq!(pots[0].hand, Some("One Pair".to_string()));
+
+    let mut side = vec![seats[1], seats[2], seats[3]];
+    side.sort();
+    assert_eq!((pots[1].size, &pots[1].eligible, &pots[1].winners), (150, &side, &vec![seats[1]]));
+
+    let mut last = vec![seats[2], seats[3]];
+    last.sort();
+    assert_eq!((pots[2].size, &pots[2].eligible, &pots[2].winners), (200, &last, &vec![seats[3]]));
+    assert_eq!(pots[2].shares, vec![(seats[3], 200)]);
+}
+
+#[test]
+fn pot_shares_include_the_odd_chip() {
+    let (mut game, sink) = new_game(3);
+    game.set_ante(1).unwrap();
+    game.stack_deck(vec![cards("Ac Ad"), cards("2c 3d"), cards("4c 5d")], cards("Ah Kh Qd Jc Ts"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    act(&mut game, 0, Action::Fold);
+    act(&mut game, 1, Action::Call);
+    act(&mut game, 2, Action::Check);
+    check_down(&mut game, &sink);
+
+    let pot = &payouts(&sink)[0].pots[0];
+    assert_eq!(pot.size, 7);
+    assert_eq!(pot.hand, Some("Straight".to_string()));
+
+    let mut shares = pot.shares.clone();
+    shares.sort();
+    let mut expected = vec![(seats[1], 4), (seats[2], 3)];
+    expected.sort();
+    assert_eq!(shares, expected);
+}
+
+#[test]
+fn shove_over_a_short_all_in_is_returned_before_payout() {
+    let (mut game, sink) = new_game(3);
+    game.stack_deck(vec![cards("Ah As"), cards("Kh Ks"), cards("Qh Qs")], cards("2c 7d 9h Jc 3s"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    set_stack(&mut game, 0, 50);
+
+    act(&mut game, 0, Action::AllIn);
+    act(&mut game, 1, Action::AllIn);
+    act(&mut game, 2, Action::Fold);
+
+    let returned = uncalled_bets(&sink);
+    assert_eq!(returned.len(), 1);
+    assert_eq!((returned[0].player_id, returned[0].amount), (seats[1], 150));
+
+    // Only the contested money is in a pot
+    let payout = &payouts(&sink)[0];
+    assert_eq!(payout.pots.len(), 1);
+    assert_eq!(payout.pots[0].size, 102);
+    assert_eq!(won(payout, seats[0]), 102);
+    assert_eq!(won(payout, seats[1]), 0);
+    assert_eq!(game.players[&seats[1]].chips + game.players[&seats[1]].street_contrib
+               + game.players[&seats[1]].hand_contrib, 150);
+}
+
+#[test]
+fn side_pot_goes_to_best_hand_among_its_players() {
+    let (mut game, sink) = new_game(3);
+    game.stack_deck(vec![cards("Ah As"), cards("Kh Ks"), cards("Qh Qs")], cards("2c 7d 9h Jc 3s"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    set_stack(&mut game, 0, 50);
+
+    act(&mut game, 0, Action::AllIn);
+    act(&mut game, 1, Action::AllIn);
+    act(&mut game, 2, Action::Call);
+
+    // The aces can't win the side pot they didn't put in for, however good they are
+    let payout = &payouts(&sink)[0];
+    assert_eq!(won(payout, seats[0]), 150);
+    assert_eq!(won(payout, seats[1]), 300);
+    assert_eq!(won(payout, seats[2]), 0);
+}
+
+#[test]
+fn folded_chips_stay_in_the_side_pot() {
+    let (mut game, sink) = new_game(3);
+    game.stack_deck(vec![cards("Ah As"), cards("2c 3d"), cards("Kh Ks")], cards("5c 7d 9h Jc Qs"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    set_stack(&mut game, 0, 50);
+
+    act(&mut game, 0, Action::AllIn);
+    act(&mut game, 1, Action::Bet(149));
+    act(&mut game, 2, Action::Call);
+    act(&mut game, 1, Action::Bet(40));
+    act(&mut game, 2, Action::Fold);
+    check_down(&mut game, &sink);
+
+    // Main pot of 3 x 50, and the rest of what the kings put in goes to the only player left
+    // in the side pot. The last bet was never called, so it goes back.
+    let payout = &payouts(&sink)[0];
+    assert_eq!(won(payout, seats[0]), 150);
+    assert_eq!(won(payout, seats[1]), 200);
+    assert_eq!(won(payout, seats[2]), 0);
+}
+
+#[test]
+fn busted_players_dont_stop_a_hand_being_won_by_a_fold() {
+    let (mut game, sink) = new_game(3);
+    knock_out(&mut game, &sink, 0, 2);
+
+    let to_act = game.to_act;
+    game.player_action(to_act, Action::Fold).unwrap();
+
+    let payout = payouts(&sink).pop().unwrap();
+    assert_eq!(payouts(&sink).len(), 2);
+    assert_eq!(payout.reason, "All others folded");
+    assert!(payout.showdown_order.is_empty());
+    assert!(payout.hole_cards.is_empty());
+}
+
+#[test]
+fn busted_seat_left_of_the_button_doesnt_show_first() {
+    let (mut game, sink) = new_game(4);
+    let seats = knock_out(&mut game, &sink, 2, 3);
+
+    // The button stays with last hand's small blind, and the busted big blind's seat is next
+    assert_eq!(seat(&game, 0), seats[1]);
+    game.player_action(seats[0], Action::Call).unwrap();
+    game.player_action(seats[1], Action::Call).unwrap();
+    game.player_action(seats[3], Action::Check).unwrap();
+    check_down(&mut game, &sink);
+
+    let payout = &payouts(&sink)[1];
+    assert_eq!(payout.showdown_order, vec![seats[3], seats[0], seats[1]]);
+    assert_eq!(shown(payout)[0], seats[3]);
+}
+
+#[test]
+fn all_in_for_part_of_the_ante_only_wins_that_much_of_each_ante() {
+    let (mut game, sink) = new_game(3);
+    game.set_ante(2).unwrap();
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    // The button folds with 1 chip left, then has the best hand when they can only ante 1 of 2
+    game.stack_deck(vec![cards("Kh Kd"), cards("2c 3d"), cards("Ah As")], cards("7c 8d 9h Jc 4s"));
+    set_stack(&mut game, 0, 3);
+    act(&mut game, 0, Action::Fold);
+    act(&mut game, 1, Action::Fold);
+    assert!(game.players[&seats[0]].all_in);
+
+    while payouts(&sink).len() == 1 {
+        let to_act = game.to_act;
+        game.player_action(to_act, Action::Call).unwrap();
+    }
+
+    // 1 from each player's ante, and the kings take the rest
+    let payout = &payouts(&sink)[1];
+    assert_eq!(won(payout, seats[0]), 3);
+    assert_eq!(won(payout, seats[2]), 0);
+}
@@ -0,0 +1,144 @@
+use game::{Action, Game};
+use super::*;
+
+/// What a player has put in on this street
+fn posted(game : &Game, player_id : usize) -> usize {
+    game.players[&player_id].street_contrib
+}
+
+#[test]
+fn heads_up_button_posts_small_blind_and_acts_first_preflop_only() {
+    let (mut game, _sink) = new_game(2);
+    game.start().unwrap();
+    let (button, big_blind) = (seat(&game, 0), seat(&game, 1));
+
+    assert_eq!(posted(&game, button), 1);
+    assert_eq!(posted(&game, big_blind), 2);
+    assert_eq!(game.to_act, button);
+
+    game.player_action(button, Action::Call).unwrap();
+    game.player_action(big_blind, Action::Check).unwrap();
+
+    // The button acts last after the flop
+    assert_eq!(game.to_act, big_blind);
+    game.player_action(big_blind, Action::Check).unwrap();
+    assert_eq!(game.to_act, button);
+}
+
+#[test]
+fn heads_up_blinds_alternate() {
+    let (mut game, _sink) = new_game(2);
+    game.start().unwrap();
+    let (button, big_blind) = (seat(&game, 0), seat(&game, 1));
+
+    game.player_action(button, Action::Fold).unwrap();
+
+    assert_eq!(seat(&game, 0), big_blind);
+    assert_eq!(posted(&game, big_blind), 1);
+    assert_eq!(posted(&game, button), 2);
+    assert_eq!(game.to_act, big_blind);
+}
+
+#[test]
+fn three_to_two_when_the_button_busts() {
+    let (mut game, sink) = new_game(3);
+    let seats = knock_out(&mut game, &sink, 0, 2);
+
+    // Last hand's big blind takes the button and small blind, and the big blind moves on
+    assert_eq!(seat(&game, 0), seats[2]);
+    assert_eq!(posted(&game, seats[2]), 1);
+    assert_eq!(posted(&game, seats[1]), 2);
+    assert_eq!(game.to_act, seats[2]);
+
+    act(&mut game, 0, Action::Call);
+    game.player_action(seats[1], Action::Check).unwrap();
+    assert_eq!(game.to_act, seats[1]);
+}
+
+#[test]
+fn three_to_two_when_the_small_blind_busts() {
+    let (mut game, sink) = new_game(3);
+    let seats = knock_out(&mut game, &sink, 1, 2);
+
+    assert_eq!(seat(&game, 0), seats[2]);
+    assert_eq!(posted(&game, seats[2]), 1);
+    assert_eq!(posted(&game, seats[0]), 2);
+    assert_eq!(game.to_act, seats[2]);
+}
+
+#[test]
+fn three_to_two_when_the_big_blind_busts() {
+    let (mut game, sink) = new_game(3);
+    let seats = knock_out(&mut game, &sink, 2, 0);
+
+    // The big blind still moves on one player, so last hand's small blind posts it again
+    assert_eq!(seat(&game, 0), seats[1]);
+    assert_eq!(posted(&game, seats[1]), 1);
+    assert_eq!(posted(&game, seats[0]), 2);
+    assert_eq!(game.to_act, seats[1]);
+}
+
+#[test]
+fn dead_small_blind_then_dead_button_when_the_big_blind_busts() {
+    let (mut game, sink) = new_game(4);
+    let seats = knock_out(&mut game, &sink, 2, 3);
+
+    // Nobody posts the small blind, and the button stays with last hand's small blind
+    assert_eq!(seat(&game, 0), seats[1]);
+    assert_eq!(posted(&game, seats[3]), 2);
+    assert_eq!(posted(&game, seats[0]), 0);
+    assert_eq!(posted(&game, seats[1]), 0);
+    assert_eq!(game.to_act, seats[0]);
+
+    game.player_action(seats[0], Action::Fold).unwrap();
+    game.player_action(seats[1], Action::Fold).unwrap();
+
+    // Then the button goes to the empty seat
+    assert_eq!(seat(&game, 0), seats[2]);
+    assert_eq!(posted(&game, seats[3]), 1);
+    assert_eq!(posted(&game, seats[0]), 2);
+    assert_eq!(game.to_act, seats[1]);
+}
+
+#[test]
+fn hand_goes_on_after_two_players_bust() {
+    let (mut game, sink) = new_game(4);
+    game.stack_deck(vec![cards("2c 3d"), cards("2d 3c"), cards("Ah As"), cards("Kh Kd")],
+                    cards("7c 8d 9h Jc 4s"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    set_stack(&mut game, 0, 10);
+    set_stack(&mut game, 1, 10);
+
+    act(&mut game, 3, Action::Fold);
+    act(&mut game, 0, Action::AllIn);
+    act(&mut game, 1, Action::AllIn);
+
+    // The big blind has nobody left to bet against once they call, so the board runs out
+    let seen = sink.player_events(seats[2]).len();
+    act(&mut game, 2, Action::Call);
+
+    let mut streets = Vec::new();
+    for event in sink.player_events(seats[2]).into_iter().skip(seen) {
+        match event {
+            GameEvent::Street(info) => streets.push(info.street),
+            GameEvent::ToMove(info) => panic!("player {} was asked to act in the runout", info.player_id),
+            GameEvent::Payout(_) => break,
+            _ => (),
+        }
+    }
+    assert_eq!(streets, vec!["Flop", "Turn", "River"]);
+
+    assert!(game.players[&seats[0]].eliminated);
+    assert!(game.players[&seats[1]].eliminated);
+
+    // The busted players went all-in, but the two left haven't, so the next hand waits on them
+    assert_eq!(payouts(&sink).len(), 1);
+    assert!(!game.is_over());
+    assert_eq!(game.to_act, seats[2]);
+
+    game.player_action(seats[2], Action::Call).unwrap();
+    assert_eq!(game.to_act, seats[3]);
+    assert_eq!(payouts(&sink).len(), 1);
+}
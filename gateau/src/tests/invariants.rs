@@ -0,0 +1,119 @@
+//! Property tests: whole games of random legal moves never make or lose chips,
+//! and never leave the game waiting on someone who can't move
+
+use game::{Action, BlindLevel, Game, LegalActions};
+use proptest::prelude::*;
+
+/// Stop a game after this many moves if nobody's won
+const MAX_MOVES : usize = 2000;
+
+/// A move picked before we know what's legal. It's turned into a legal one when it's played.
+#[derive(Debug, Clone)]
+enum Choice {
+    Fold,
+    CheckOrCall,
+    Raise(usize), // Percent of the way from the smallest raise to all-in
+    AllIn,
+}
+
+#[derive(Debug, Clone)]
+struct Setup {
+    num_players : usize,
+    seed : u64,
+    stack : usize,
+    small_blind : usize,
+    ante : usize,
+    big_blind_ante : bool,
+    level_hands : usize,
+}
+
+fn choice() -> BoxedStrategy<Choice> {
+    prop_oneof![
+        Just(Choice::Fold),
+        Just(Choice::CheckOrCall),
+        (0usize..101).prop_map(Choice::Raise),
+        Just(Choice::AllIn),
+    ].boxed()
+}
+
+fn setup() -> BoxedStrategy<Setup> {
+    (2usize..7, any::<u64>(), 1usize..301, 1usize..6, 0usize..4, any::<bool>(), 0usize..6)
+        .prop_map(|(num_players, seed, stack, small_blind, ante, big_blind_ante, level_hands)| {
+            Setup {
+                num_players,
+                seed,
+                stack,
+                small_blind,
+                ante,
+                big_blind_ante,
+                level_hands,
+            }
+        })
+        .boxed()
+}
+
+fn new_game(setup : &Setup) -> Game {
+    let mut game = Game::new(setup.stack);
+
+    game.set_seed(setup.seed).unwrap();
+    game.set_big_blind(setup.small_blind * 2).unwrap();
+    game.set_small_blind(setup.small_blind).unwrap();
+    game.set_ante(setup.ante).unwrap();
+    game.set_big_blind_ante(setup.big_blind_ante).unwrap();
+    game.set_level_hands(setup.level_hands).unwrap();
+    game.set_blind_schedule((2..6).map(|level| BlindLevel {
+                                       small_blind : setup.small_blind * level,
+                                       big_blind : setup.small_blind * level * 2,
+                                       ante : setup.ante * level,
+                                   })
+                                   .collect()).unwrap();
+
+    for id in 0..setup.num_players {
+        game.add_player(&format!("Player {}",id)).unwrap();
+    }
+
+    // We check after every move ourselves, so a broken invariant shrinks rather than panics
+    game.set_invariant_checks(false);
+
+    game
+}
+
+/// The legal move closest to what was picked
+fn legal_move(choice : &Choice, legal : &LegalActions, street_contrib : usize) -> Action {
+    match (choice, legal.min_raise_to, legal.max_raise_to) {
+        (&Choice::Fold, _, _) if !legal.can_check => Action::Fold,
+        (&Choice::Raise(percent), Some(min), Some(max)) => {
+            Action::Bet(min + (max - min) * percent / 100 - street_contrib)
+        },
+        (&Choice::AllIn, _, _) => Action::AllIn,
+        _ => Action::Call,
+    }
+}
+
+proptest! {
+    #[test]
+    fn random_games_keep_the_invariants(ref setup in setup(),
+                                        ref choices in prop::collection::vec(choice(), 1..200)) {
+        ::set_logging(false);
+
+        let mut game = new_game(setup);
+        game.start().unwrap();
+        prop_assert_eq!(game.check_invariants(), Ok(()));
+
+        for (moves, choice) in choices.iter().cycle().enumerate() {
+            if game.is_over() || moves == MAX_MOVES {
+                break;
+            }
+
+            let legal = game.legal_actions().unwrap();
+            let street_contrib = game.players[&legal.player_id].street_contrib;
+            let action = legal_move(choice, &legal, street_contrib);
+
+            game.player_action(legal.player_id, action).unwrap();
+
+            let checked = game.check_invariants();
+            prop_assert!(checked.is_ok(), "after {:?} by player {} on move {}: {:?}",
+                         action, legal.player_id, moves, checked);
+        }
+    }
+}
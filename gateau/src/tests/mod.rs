@@ -0,0 +1,131 @@
+//! Scenario tests for the rules engine, and the helpers they share
+
+mod actions;
+mod config;
+mod end_hand;
+mod heads_up;
+mod invariants;
+mod replay;
+mod sinks;
+
+use events::{GameEvent, PayoutInfo, RecordingSink, UncalledBetInfo};
+use game::{Action, Game};
+use rs_poker::core::{Card, Suit, Value};
+use std::sync::Arc;
+
+/// Cards written like "Ah Td 2c"
+pub fn cards(cards : &str) -> Vec<Card> {
+    cards.split_whitespace()
+         .map(|card| {
+             let mut chars = card.chars();
+             Card {
+                 value : chars.next().and_then(Value::from_char).unwrap(),
+                 suit  : chars.next().and_then(Suit::from_char).unwrap(),
+             }
+         })
+         .collect()
+}
+
+/// A game with `num_players` players and 1/2 blinds that checks its invariants after every move,
+/// and a sink that remembers everything
+pub fn new_game(num_players : usize) -> (Game, Arc<RecordingSink>) {
+    let sink = Arc::new(RecordingSink::new());
+    let mut game = Game::with_sink(200, sink.clone());
+    game.set_invariant_checks(true);
+
+    for id in 0..num_players {
+        game.add_player(&format!("Player {}",id)).unwrap();
+    }
+
+    (game, sink)
+}
+
+/// The player in a seat, counting from the button
+pub fn seat(game : &Game, seat : usize) -> usize {
+    game.seat_order[seat]
+}
+
+/// Make a move for the player in `seat`, checking it's their turn
+pub fn act(game : &mut Game, seat_number : usize, action : Action) {
+    let player_id = seat(game, seat_number);
+    assert_eq!(game.to_act, player_id, "expected seat {} to act", seat_number);
+
+    game.player_action(player_id, action).unwrap();
+}
+
+/// Give the player in `seat` a stack of `chips`, counting whatever they've put in already.
+/// Stacks can only be set once seats are drawn, which is after the blinds go in.
+pub fn set_stack(game : &mut Game, seat_number : usize, chips : usize) {
+    let player_id = seat(game, seat_number);
+    game.set_stack(player_id, chips);
+}
+
+/// Every payout message sent so far
+pub fn payouts(sink : &RecordingSink) -> Vec<PayoutInfo> {
+    sink.public_events()
+        .into_iter()
+        .filter_map(|event| match event {
+            GameEvent::Payout(payout) => Some(payout),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Every uncalled bet given back so far
+pub fn uncalled_bets(sink : &RecordingSink) -> Vec<UncalledBetInfo> {
+    sink.public_events()
+        .into_iter()
+        .filter_map(|event| match event {
+            GameEvent::UncalledBet(uncalled) => Some(uncalled),
+            _ => None,
+        })
+        .collect()
+}
+
+/// What a player won in a payout
+pub fn won(payout : &PayoutInfo, player_id : usize) -> usize {
+    payout.payouts.iter()
+                  .find(|&&(id, _)| id == player_id)
+                  .map(|&(_, amount)| amount)
+                  .unwrap()
+}
+
+/// Everyone checks until the hand is over, starting with whoever's to act
+pub fn check_down(game : &mut Game, sink : &RecordingSink) {
+    let hands_paid = payouts(sink).len();
+
+    while payouts(sink).len() == hands_paid {
+        let to_act = game.to_act;
+        game.player_action(to_act, Action::Check).unwrap();
+    }
+}
+
+/// Start the game, and play a first hand where the player in seat `loser` goes all-in for 10,
+/// is called by the player in seat `winner` and is knocked out. Everyone else folds.
+/// Returns who was in each seat for that hand.
+pub fn knock_out(game : &mut Game, sink : &RecordingSink, loser : usize, winner : usize) -> Vec<usize> {
+    let mut hole_cards = vec![cards("Kh Kd"), cards("Qh Qd"), cards("Th Td"), cards("5h 5d")];
+    hole_cards.truncate(game.seat_order.len());
+    hole_cards[loser] = cards("2c 3d");
+    hole_cards[winner] = cards("Ah As");
+    game.stack_deck(hole_cards, cards("7c 8d 9h Jc 4s"));
+    game.start().unwrap();
+    let seats = game.seat_order.clone();
+
+    set_stack(game, loser, 10);
+
+    while payouts(sink).is_empty() {
+        let to_act = game.to_act;
+        let action = if to_act == seats[loser] {
+            Action::AllIn
+        } else if to_act == seats[winner] {
+            Action::Call
+        } else {
+            Action::Fold
+        };
+        game.player_action(to_act, action).unwrap();
+    }
+
+    assert!(game.players[&seats[loser]].eliminated);
+    seats
+}
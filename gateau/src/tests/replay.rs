@@ -0,0 +1,90 @@
+//! Replaying a game's record tells everyone the same things again, and the seed
+//! revealed at the end matches what was promised at the start
+
+use super::*;
+use events::Recipient;
+use sha2::{Digest, Sha256};
+
+/// Stop a game after this many moves if nobody's won
+const MAX_MOVES : usize = 1000;
+
+/// Play a seeded game to the end, shoving, calling and folding in turn
+fn play_seeded_game(seed : u64) -> (Game, Arc<RecordingSink>) {
+    let (mut game, sink) = new_game(3);
+    game.set_seed(seed).unwrap();
+    game.set_starting_stack(40).unwrap();
+    game.start().unwrap();
+
+    for moves in 0..MAX_MOVES {
+        if game.is_over() {
+            break;
+        }
+
+        let legal = game.legal_actions().unwrap();
+        let action = match moves % 3 {
+            0 => Action::AllIn,
+            2 if !legal.can_check => Action::Fold,
+            _ => Action::Call,
+        };
+        game.player_action(legal.player_id, action).unwrap();
+    }
+
+    assert!(game.is_over(), "game still going after {} moves", MAX_MOVES);
+    (game, sink)
+}
+
+/// Every event, leaving out what's allowed to change between replays:
+/// secret IDs, and how much time players had
+fn comparable(events : Vec<(Recipient, GameEvent)>) -> Vec<String> {
+    events.into_iter()
+          .map(|(to, mut event)| {
+              match event {
+                  GameEvent::PlayerPrivate(ref mut info) => info.secret_id.clear(),
+                  GameEvent::ToMove(ref mut info) => info.time_bank = 0.0,
+                  _ => (),
+              }
+              format!("{:?} {:?}", to, event)
+          })
+          .collect()
+}
+
+#[test]
+fn replay_sends_the_same_events() {
+    let (game, sink) = play_seeded_game(42);
+    let record = game.record().unwrap().clone();
+
+    let replay_sink = Arc::new(RecordingSink::new());
+    let replayed = Game::replay(&record, replay_sink.clone()).unwrap();
+
+    assert!(replayed.is_over());
+    assert!(!record.moves.is_empty());
+    assert_eq!(comparable(replay_sink.events()), comparable(sink.events()));
+}
+
+#[test]
+fn revealed_seed_hashes_to_the_seed_hash_sent_at_the_start() {
+    let (_game, sink) = play_seeded_game(0x0123_4567_89ab_cdef);
+    let events = sink.public_events();
+
+    let seed_hash = events.iter()
+                          .filter_map(|event| match *event {
+                              GameEvent::GameTable(ref info) => Some(info.seed_hash.clone()),
+                              _ => None,
+                          })
+                          .next()
+                          .unwrap();
+    let seed = events.iter()
+                     .filter_map(|event| match *event {
+                         GameEvent::GameOver(ref info) => Some(info.seed),
+                         _ => None,
+                     })
+                     .next()
+                     .unwrap();
+
+    assert_eq!(seed, 0x0123_4567_89ab_cdef);
+
+    let hashed : String = Sha256::digest(&seed.to_le_bytes()).iter()
+                                                            .map(|byte| format!("{:02x}", byte))
+                                                            .collect();
+    assert_eq!(hashed, seed_hash);
+}
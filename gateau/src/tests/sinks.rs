@@ -0,0 +1,80 @@
+//! Sinks get every event, for the right recipient, in the order the game sent them
+
+use super::*;
+use events::{ChannelSink, Recipient};
+
+/// Which kind of event each one is
+fn kinds(events : &[GameEvent]) -> Vec<&'static str> {
+    events.iter()
+          .map(|event| match *event {
+              GameEvent::PlayerPrivate(_) => "PlayerPrivate",
+              GameEvent::GameTable(_) => "GameTable",
+              GameEvent::HoleCards(_) => "HoleCards",
+              GameEvent::Move(_) => "Move",
+              GameEvent::UncalledBet(_) => "UncalledBet",
+              GameEvent::ToMove(_) => "ToMove",
+              GameEvent::Street(_) => "Street",
+              GameEvent::BlindLevel(_) => "BlindLevel",
+              GameEvent::Payout(_) => "Payout",
+              GameEvent::PlayerEliminated(_) => "PlayerEliminated",
+              GameEvent::GameOver(_) => "GameOver",
+          })
+          .collect()
+}
+
+/// Start a three handed game and have the first player to act fold
+fn start_and_fold(game : &mut Game) {
+    game.set_seed(7).unwrap();
+    game.start().unwrap();
+
+    let to_act = game.to_act;
+    game.player_action(to_act, Action::Fold).unwrap();
+}
+
+/// What every player should be sent by `start_and_fold`
+const PLAYER_KINDS : [&str; 9] = ["PlayerPrivate", "GameTable", "HoleCards", "Move", "Move",
+                                  "Street", "ToMove", "Move", "ToMove"];
+
+/// What anyone watching should be sent by `start_and_fold`
+const PUBLIC_KINDS : [&str; 7] = ["GameTable", "Move", "Move", "Street", "ToMove", "Move", "ToMove"];
+
+#[test]
+fn recording_sink_keeps_events_in_order() {
+    let (mut game, sink) = new_game(3);
+    start_and_fold(&mut game);
+
+    // Hole cards only go to their own player
+    for player_id in 0..3 {
+        assert_eq!(kinds(&sink.player_events(player_id)), PLAYER_KINDS);
+    }
+    assert_eq!(kinds(&sink.public_events()), PUBLIC_KINDS);
+
+    // Each event goes to everyone before the next one goes to anybody
+    let recipients : Vec<Recipient> = sink.events().into_iter().map(|(recipient, _)| recipient).collect();
+    assert_eq!(recipients[3..7].to_vec(),
+               vec![Recipient::Public, Recipient::Player(0), Recipient::Player(1), Recipient::Player(2)]);
+}
+
+#[test]
+fn channel_sink_sends_events_in_order() {
+    let (sink, receiver) = ChannelSink::new();
+    let mut game = Game::with_sink(200, Arc::new(sink));
+    for id in 0..3 {
+        game.add_player(&format!("Player {}",id)).unwrap();
+    }
+    start_and_fold(&mut game);
+
+    let mut player_events = vec![Vec::new(), Vec::new(), Vec::new()];
+    let mut public_events = Vec::new();
+    for (recipient, event) in receiver.try_iter() {
+        match recipient {
+            Recipient::Player(player_id) => player_events[player_id].push(event),
+            Recipient::Public => public_events.push(event),
+        }
+    }
+
+    for events in &player_events {
+        assert_eq!(kinds(events), PLAYER_KINDS);
+    }
+    assert_eq!(kinds(&public_events), PUBLIC_KINDS);
+}
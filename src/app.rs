@@ -1,8 +1,7 @@
 #![feature(plugin)]
 #![plugin(rocket_codegen)]
 
-extern crate rs_poker;
-extern crate rand;
+#[macro_use] extern crate gateau;
 extern crate uuid;
 extern crate rocket;
 #[macro_use] extern crate rocket_contrib;
@@ -11,33 +10,56 @@ extern crate serde;
 extern crate serde_json;
 extern crate reqwest;
 extern crate hyper;
+extern crate ws;
 
-mod game;
-mod player;
+mod delivery;
+mod socket;
 
 use std::collections::HashMap;
 use rocket_contrib::{Json, Value};
-use rocket::{State};
-use game::{Game,Action};
-use std::sync::RwLock;
-use std::ops::{Deref, DerefMut};
+use rocket::{Config, State};
+use rocket::request::Request;
+use rocket::response::{self, Responder, status};
+use rocket::http::Status;
+use gateau::game::{Game,Action,BlindLevel};
+use gateau::error::GameError;
+use gateau::events::NotificationSink;
+use delivery::HttpSink;
+use std::process;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
 use uuid::Uuid;
 
-//#[derive(Serialize, Deserialize)]
-//struct CreateGame {
-//    game_id : String,
-//}
-//
-//#[derive(Serialize, Deserialize)]
-//struct DeleteGame {
-//    game_id : String, // TODO: authentication
-//}
+/// A game on the server, and how its events get to players
+struct Table {
+    game : Game,
+    delivery : Arc<HttpSink>,
+}
+
+/// All games on the server, by game ID
+type GameMap = Arc<RwLock<HashMap<String,Table>>>;
+
+/// Chips players start with unless the game is configured otherwise. 100 big blinds.
+const DEFAULT_STARTING_STACK : usize = 200;
+
+/// How often games check their move timers
+const CLOCK_TICK_MS : u64 = 100;
+
+/// How long a request to /events waits for something to happen. Each waiting request
+/// holds one of Rocket's workers, so keep this short and have clients poll again.
+const POLL_TIMEOUT_SECS : u64 = 2;
+
+/// Port players and spectators connect to over WebSockets, unless `websocket_port` is configured
+const DEFAULT_WEBSOCKET_PORT : i64 = 8001;
 
 #[derive(Serialize, Deserialize)]
 struct GameConfig {
-    game_id : String,
-    config  : String, // Field to modify. At the moment just 'starting_stack'
+    game_id     : String,
+    admin_token : String, // Must match the token given when the game was created
+    config  : String, // Field to modify, e.g. 'starting_stack'
     value   : usize,
+    levels  : Option<Vec<BlindLevel>>, // Blind levels, only used by 'blind_schedule'
 }
 
 #[derive(Serialize, Deserialize)]
@@ -52,129 +74,432 @@ struct PlayerMessage {
 struct JoinData {
     game_id : String,
     name    : String, // Display name for the player
-    address : String, // IP address of the player
+    address : Option<String>, // IP address of the player. Without it, the player polls /events instead.
+    #[serde(default)]
+    always_show : bool, // Show the hand at every showdown, even when it could be mucked
 }
 
-#[post("/config", format="application/json", data="<game_config>")]
-fn configure_game(game_config: Json<GameConfig>, game_lock: State<RwLock<HashMap<String,Game>>>) -> Json<Value> {
+#[derive(FromForm)]
+struct PlayerQuery {
+    game_id   : String,
+    secret_id : String,
+}
+
+#[derive(FromForm)]
+struct AdminQuery {
+    admin_token : String,
+}
+
+#[derive(FromForm)]
+struct SecretQuery {
+    secret_id : String,
+}
+
+#[derive(FromForm)]
+struct EventQuery {
+    game_id   : String,
+    secret_id : String,
+    since     : Option<usize>, // First message number wanted
+}
+
+/// Create a new game. Whoever creates it gets the admin token needed to configure or delete it.
+#[post("/games")]
+fn create_game(game_lock: State<GameMap>) -> Json<Value> {
+    let mut games = game_lock.write().unwrap();
+
+    let game_id = Uuid::new_v4().simple().to_string();
+    let delivery = Arc::new(HttpSink::default());
+    let game = Game::with_sink(DEFAULT_STARTING_STACK, delivery.clone());
+    let admin_token = game.admin_token();
+
+    games.insert(game_id.clone(), Table {
+        game : game,
+        delivery : delivery,
+    });
+    log!("DEBUG - Created game {}",game_id);
+
+    Json(json!({
+        "status" : "ok",
+        "game_id" : game_id,
+        "admin_token" : admin_token.simple().to_string(),
+    }))
+}
+
+/// List every game on the server
+#[get("/games")]
+fn list_games(game_lock: State<GameMap>) -> Json<Value> {
+    let games = game_lock.read().unwrap();
+
+    let game_list = games.iter()
+                         .map(|(game_id, table)| json!({
+                             "game_id" : game_id,
+                             "status" : table.game.status(),
+                             "players" : table.game.players.len(),
+                             "max_players" : table.game.max_players(),
+                         }))
+                         .collect::<Vec<_>>();
+
+    Json(json!({
+        "status" : "ok",
+        "games" : game_list,
+    }))
+}
+
+/// Delete a game, given its admin token
+#[delete("/games/<game_id>?<query>")]
+fn delete_game(game_id: String, query: AdminQuery, game_lock: State<GameMap>) -> Result<Json<Value>, ApiError> {
     let mut games = game_lock.write().unwrap();
 
-    if !games.deref().contains_key(&game_config.game_id) {
-        games.deref_mut().insert(game_config.game_id.clone(), Game::new(200));
+    {
+        let game = &games.get(&game_id).ok_or(GameError::UnknownGame)?.game;
+        let admin_token = Uuid::parse_str(&query.admin_token).map_err(|_| GameError::WrongAdminToken)?;
+        if !game.is_admin(&admin_token) {
+            return Err(GameError::WrongAdminToken.into());
+        }
+    }
+
+    games.remove(&game_id);
+    log!("DEBUG - Deleted game {}",game_id);
+
+    Ok(Json(json!({
+        "status" : "ok",
+    })))
+}
+
+/// Everything needed to replay a finished game
+#[get("/games/<game_id>/record")]
+fn game_record(game_id: String, game_lock: State<GameMap>) -> Result<Json<Value>, ApiError> {
+    let games = game_lock.read().unwrap();
+
+    let game = &games.get(&game_id).ok_or(GameError::UnknownGame)?.game;
+
+    // The record gives away the seed, and with it every card
+    if !game.is_over() {
+        return Err(GameError::NotOver.into());
     }
 
-    let mut game = games.get_mut(&game_config.game_id).unwrap();
+    Ok(Json(json!({
+        "status" : "ok",
+        "record" : game.record(),
+    })))
+}
+
+#[post("/config", format="application/json", data="<game_config>")]
+fn configure_game(game_config: Json<GameConfig>, game_lock: State<GameMap>) -> Result<Json<Value>, ApiError> {
+    let mut games = game_lock.write().unwrap();
+
+    let table = games.get_mut(&game_config.game_id).ok_or(GameError::UnknownGame)?;
+    let (game, delivery) = (&mut table.game, &table.delivery);
+
+    // A token that isn't even a UUID is just as wrong as someone else's
+    let is_admin = Uuid::parse_str(&game_config.admin_token).map(|token| game.is_admin(&token)).unwrap_or(false);
+    if !is_admin {
+        log!("DEBUG - Bad admin token for game {}",game_config.game_id);
+        return Err(GameError::WrongAdminToken.into());
+    }
 
     match game_config.config.to_lowercase().as_ref() {
-        // TODO: 
-        // - Make move timers configurable
-        "starting_stack" => {
-            let success = (*game).set_starting_stack(game_config.value);
-            if !success {
-                return Json(json!({
-                    "status" : "error",
-                    "reason" : "Game already started!",
-                }));
-            }
+        "starting_stack"         => game.set_starting_stack(game_config.value)?,
+        "max_players"            => game.set_player_limit(game_config.value)?,
+        "small_blind"            => game.set_small_blind(game_config.value)?,
+        "big_blind"              => game.set_big_blind(game_config.value)?,
+        "ante"                   => game.set_ante(game_config.value)?,
+        "big_blind_ante"         => game.set_big_blind_ante(game_config.value != 0)?,
+        "blind_schedule"         => {
+            let levels = game_config.levels.clone().ok_or(GameError::InvalidConfig)?;
+            game.set_blind_schedule(levels)?
         },
-        "max_players" => {
-            let success = (*game).set_player_limit(game_config.value);
-            if !success {
-                return Json(json!({
-                    "status" : "error",
-                    "reason" : "Game already started!",
-                }));
-            }
+        "blind_level_hands"      => game.set_level_hands(game_config.value)?,
+        "blind_level_minutes"    => game.set_level_minutes(game_config.value as u64)?,
+        "move_time"              => game.set_move_time(game_config.value as u64)?,
+        "time_bank"              => game.set_time_bank(game_config.value as u64)?,
+        "time_bank_refill"       => game.set_bank_refill(game_config.value as u64)?,
+        "time_bank_refill_hands" => game.set_bank_refill_hands(game_config.value)?,
+        "seed"                   => game.set_seed(game_config.value as u64)?,
+        "push_retries"           => {
+            not_started(game)?;
+            delivery.set_retries(game_config.value)
         },
-        "start" => {
-            let success = (*game).start();
-            if !success {
-                return Json(json!({
-                    "status" : "error",
-                    "reason" : "Game already started!",
-                }));
-            }
+        "push_backoff_ms"        => {
+            not_started(game)?;
+            delivery.set_backoff(game_config.value as u64)
         },
+        "disconnect_after"       => {
+            not_started(game)?;
+            delivery.set_disconnect_after(game_config.value)
+        },
+        "start"                  => game.start()?,
         other => {
-            println!("DEBUG - Bad config option: {}",other);
-            return Json(json!({
-                "status" : "error",
-                "reason" : "Bad config option!"
-            }));
+            log!("DEBUG - Bad config option: {}",other);
+            return Err(GameError::InvalidConfig.into());
         },
     }
 
-    Json(json!({
+    Ok(Json(json!({
         "status" : "ok",
-    }))
+    })))
 }
 
 #[post("/reg", format="application/json", data="<reg_data>")]
-fn join_game(reg_data: Json<JoinData>, game_lock: State<RwLock<HashMap<String,Game>>>) -> Json<Value> {
+fn join_game(reg_data: Json<JoinData>, game_lock: State<GameMap>) -> Result<Json<Value>, ApiError> {
+    let mut games = game_lock.write().unwrap();
+
+    let table = games.get_mut(&reg_data.game_id).ok_or(GameError::UnknownGame)?;
+
+    let id = table.game.add_player(reg_data.name.as_ref())?;
+    table.game.set_always_show(id, reg_data.always_show)?;
+    table.delivery.add_player(id, reg_data.name.as_ref(), reg_data.address.as_ref().map(String::as_ref));
+
+    // Players without an address need their secret ID now so they can poll /events
+    Ok(Json(json!({
+        "status" : "ok",
+        "ingame_id" : id,
+        "secret_id" : table.game.players[&id].secret_id.simple().to_string(),
+    })))
+}
+
+#[post("/game", format="application/json", data="<action>")]
+fn make_move(action: Json<PlayerMessage>, game_lock: State<GameMap>) -> Result<Json<Value>, ApiError> {
     let mut games = game_lock.write().unwrap();
 
-    if !games.deref().contains_key(&reg_data.game_id) {
-        games.deref_mut().insert(reg_data.game_id.clone(), Game::new(200));
+    let table = games.get_mut(&action.game_id).ok_or(GameError::UnknownGame)?;
+
+    apply_move(&mut table.game, &action)?;
+
+    Ok(Json(json!({
+        "status" : "ok",
+    })))
+}
+
+/// Apply a player's move to their game, whether it came over HTTP or a WebSocket
+fn apply_move(game: &mut Game, action: &PlayerMessage) -> Result<(), GameError> {
+    let player_id = authenticate(game, &action.game_id, &action.secret_id)?;
+
+    let recv_action = match action.action.to_lowercase().as_ref() {
+        "check" => Action::Check,
+        "call"  => Action::Call,
+        "fold"  => Action::Fold,
+        "allin" => Action::AllIn,
+        "bet"   => Action::Bet(action.value),
+        other   => {
+            log!("DEBUG - Invalid action recieved {}",other);
+            return Err(GameError::InvalidAction);
+        },
+    };
+
+    let result = game.player_action(player_id, recv_action);
+    if result == Err(GameError::NotYourTurn) {
+        println!("AUDIT - Player {} tried to move out of turn in game {} (waiting on player {})",player_id,action.game_id,game.to_act);
     }
 
-    let mut game = games.get_mut(&reg_data.game_id).unwrap();
+    result
+}
+
+/// Everything about a game that isn't secret
+#[get("/game/<game_id>")]
+fn game_state(game_id: String, game_lock: State<GameMap>) -> Result<Json<Value>, ApiError> {
+    let games = game_lock.read().unwrap();
+
+    let game = &games.get(&game_id).ok_or(GameError::UnknownGame)?.game;
+
+    Ok(Json(json!({
+        "status" : "ok",
+        "game" : game.public_state(),
+    })))
+}
+
+/// What the player to act can do
+#[get("/game/<game_id>/legal")]
+fn legal_actions(game_id: String, game_lock: State<GameMap>) -> Result<Json<Value>, ApiError> {
+    let games = game_lock.read().unwrap();
+
+    let game = &games.get(&game_id).ok_or(GameError::UnknownGame)?.game;
 
-    // Could change this to Option<PlayerInfo> or Result<PlayerInfo> and return stuff here
-    let able_to_join = (*game).add_player(reg_data.name.as_ref(),reg_data.address.as_ref());
+    Ok(Json(json!({
+        "status" : "ok",
+        "legal_actions" : game.legal_actions()?,
+    })))
+}
+
+/// Everything about a game that isn't secret, plus the asking player's own hole cards
+#[get("/game/<game_id>?<query>")]
+fn player_game_state(game_id: String, query: SecretQuery, game_lock: State<GameMap>) -> Result<Json<Value>, ApiError> {
+    let games = game_lock.read().unwrap();
+
+    let game = &games.get(&game_id).ok_or(GameError::UnknownGame)?.game;
+    let player_id = authenticate_query(game, &game_id, &query.secret_id)?;
+
+    Ok(Json(json!({
+        "status" : "ok",
+        "game" : game.public_state(),
+        "player_id" : player_id,
+        "hole_cards" : game.hole_cards(player_id),
+    })))
+}
+
+/// Long-poll for the asking player's messages, for players without an address to be sent them
+#[get("/events?<query>")]
+fn events(query: EventQuery, game_lock: State<GameMap>) -> Result<Json<Value>, ApiError> {
+    let mailbox = {
+        // Don't hold on to the games while we wait
+        let games = game_lock.read().unwrap();
+
+        let table = games.get(&query.game_id).ok_or(GameError::UnknownGame)?;
+        table.delivery.mailbox(authenticate_query(&table.game, &query.game_id, &query.secret_id)?)
+    };
+
+    // Asking for messages from `since` means the player has everything before it
+    let since = query.since.unwrap_or(0);
+    if !mailbox.drop_before(None, since) {
+        return Err(GameError::AlreadyConnected.into());
+    }
+
+    let events = mailbox.wait_since(since, Duration::from_secs(POLL_TIMEOUT_SECS));
+    let next = events.last().map(|&(seq, _)| seq + 1).unwrap_or(since);
+
+    Ok(Json(json!({
+        "status" : "ok",
+        "events" : events.into_iter()
+                         .map(|(seq, message)| json!({ "seq" : seq, "message" : message }))
+                         .collect::<Vec<_>>(),
+        "next" : next,
+    })))
+}
 
-    // TODO: POST this ID to the new player's address so they can make moves
-    // ^ put this is the add_player method...?
+/// Messages that couldn't be delivered to the asking player
+#[get("/deadletters?<query>")]
+fn dead_letters(query: PlayerQuery, game_lock: State<GameMap>) -> Result<Json<Value>, ApiError> {
+    let games = game_lock.read().unwrap();
 
-    if able_to_join {
-        return Json(json!({
-            "status" : "ok",
-        }));
+    let table = games.get(&query.game_id).ok_or(GameError::UnknownGame)?;
+    let player_id = authenticate_query(&table.game, &query.game_id, &query.secret_id)?;
+
+    Ok(Json(json!({
+        "status" : "ok",
+        "failures" : table.delivery.failures(player_id),
+        "disconnected" : table.delivery.is_disconnected(player_id),
+        "dead_letters" : table.delivery.dead_letters(player_id),
+    })))
+}
+
+/// Delivery settings belong to the server rather than the game, but can't change
+/// once the game has started either
+fn not_started(game: &Game) -> Result<(), GameError> {
+    if game.is_started() {
+        Err(GameError::AlreadyStarted)
     } else {
-        return Json(json!({
-            "status" : "error",
-            "reason" : "No space to join this game"
-        }));
+        Ok(())
     }
 }
 
-#[post("/game", format="application/json", data="<action>")]
-fn make_move(action: Json<PlayerMessage>, game_lock: State<RwLock<HashMap<String,Game>>>) -> Json<Value> {
-    let mut games = game_lock.write().unwrap();
+/// Find the player a secret ID belongs to, logging any that don't belong to anyone
+fn authenticate(game: &Game, game_id: &str, secret_id: &Uuid) -> Result<usize, GameError> {
+    let result = game.authenticate(secret_id);
+    if result.is_err() {
+        println!("AUDIT - Unknown secret ID {} used for game {}",secret_id,game_id);
+    }
+    result
+}
 
-    if !games.deref().contains_key(&action.game_id) {
-        games.deref_mut().insert(action.game_id.clone(), Game::new(200));
+/// Find the player a secret ID from a query string belongs to
+fn authenticate_query(game: &Game, game_id: &str, secret_id: &str) -> Result<usize, GameError> {
+    match Uuid::parse_str(secret_id) {
+        Ok(secret_id) => authenticate(game, game_id, &secret_id),
+        Err(_) => {
+            println!("AUDIT - Malformed secret ID {:?} used for game {}",secret_id,game_id);
+            Err(GameError::UnknownSecret)
+        },
     }
+}
 
-    let mut game = games.get_mut(&action.game_id).unwrap();
+/// The body sent back for an error, with a code clients can match on
+fn error_json(error: GameError) -> Value {
+    json!({
+        "status" : "error",
+        "error"  : error.code(),
+        "reason" : error.reason(),
+    })
+}
 
-    if action.secret_id != game.players.get(&game.to_act).unwrap().secret_id {
-        println!("DEBUG - Recieved secret ID {} does not match expected {}",action.secret_id,game.players.get(&game.to_act).unwrap().secret_id);
-        return Json(json!({
-            "status" : "error",
-            "reason" : "Not your turn!"
-        }));
+/// The HTTP status that best fits an error
+fn http_status(error: GameError) -> Status {
+    match error {
+        GameError::UnknownGame      => Status::NotFound,
+        GameError::UnknownSecret    => Status::Forbidden,
+        GameError::WrongAdminToken  => Status::Forbidden,
+        GameError::NotStarted       => Status::Conflict,
+        GameError::AlreadyStarted   => Status::Conflict,
+        GameError::GameOver         => Status::Conflict,
+        GameError::NotOver          => Status::Conflict,
+        GameError::NotYourTurn      => Status::Conflict,
+        GameError::TableFull        => Status::Conflict,
+        GameError::NotEnoughPlayers => Status::Conflict,
+        GameError::AlreadyConnected => Status::Conflict,
+        GameError::InvalidAction    => Status::BadRequest,
+        GameError::InvalidConfig    => Status::BadRequest,
     }
+}
 
-    match action.action.to_lowercase().as_ref() {
-        "check" => (*game).player_action(Action::Check),
-        "call"  => (*game).player_action(Action::Call),
-        "fold"  => (*game).player_action(Action::Fold),
-        "allin" => (*game).player_action(Action::AllIn),
-        "bet"   => (*game).player_action(Action::Bet(action.value)),
-        other   => println!("DEBUG - Invalid action recieved {}",other), // do nothing
+/// A game error on its way back to the client
+struct ApiError(GameError);
+
+impl From<GameError> for ApiError {
+    fn from(error: GameError) -> ApiError {
+        ApiError(error)
     }
+}
 
-    Json(json!({
-        "status" : "ok",
-    }))
+impl<'r> Responder<'r> for ApiError {
+    fn respond_to(self, request: &Request) -> response::Result<'r> {
+        let ApiError(error) = self;
+        status::Custom(http_status(error), Json(error_json(error))).respond_to(request)
+    }
+}
+
+/// Runs forever in the background, letting every game act on expired move timers
+fn start_game_clock(games: GameMap) {
+    thread::spawn(move || {
+        loop {
+            thread::sleep(Duration::from_millis(CLOCK_TICK_MS));
+
+            let mut games = games.write().unwrap();
+            for (_, table) in games.iter_mut() {
+                table.game.tick();
+            }
+        }
+    });
 }
 
-fn rocket() -> rocket::Rocket {
+fn rocket(games: GameMap) -> rocket::Rocket {
     rocket::ignite()
-        .mount("/",routes![configure_game, join_game, make_move])
-        .manage(RwLock::new(HashMap::<String,Game>::new())) // Default game is 100 big blinds
+        .mount("/",routes![create_game, list_games, delete_game, game_record, configure_game, join_game, make_move, game_state, player_game_state, legal_actions, events, dead_letters])
+        .manage(games)
 }
 
 fn main() {
-    rocket().launch();
+    let games : GameMap = Arc::new(RwLock::new(HashMap::new()));
+    start_game_clock(games.clone());
+
+    let rocket = rocket(games.clone());
+
+    // Set `quiet` to stop games and deliveries logging every move
+    gateau::set_logging(!rocket.config().get_bool("quiet").unwrap_or(false));
+
+    let socket_address = websocket_address(rocket.config());
+    if let Err(err) = socket::serve(&socket_address, games) {
+        println!("DEBUG - Couldn't start the WebSocket server on {}: {}",socket_address,err);
+        process::exit(1);
+    }
+
+    rocket.launch();
+}
+
+/// Where players and spectators connect over WebSockets. Set `websocket_address` and `websocket_port`
+/// in Rocket.toml, or `ROCKET_WEBSOCKET_ADDRESS` and `ROCKET_WEBSOCKET_PORT`, to change it.
+/// By default it's the address Rocket serves HTTP on, port 8001.
+fn websocket_address(config : &Config) -> String {
+    let address = config.get_str("websocket_address").unwrap_or(&config.address);
+    let port = config.get_int("websocket_port").unwrap_or(DEFAULT_WEBSOCKET_PORT);
+
+    format!("{}:{}",address,port)
 }
\ No newline at end of file
@@ -0,0 +1,397 @@
+use reqwest;
+use reqwest::header::{Headers, ContentType};
+use serde_json;
+use serde_json::Value;
+use gateau::events::{GameEvent, NotificationSink};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, Condvar};
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Messages the spectator feed keeps for spectators who connect partway through a game.
+/// Enough for the last few hands.
+const SPECTATOR_BACKLOG : usize = 1000;
+
+/// How hard to try delivering a message before giving up on it
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub retries : usize,          // Extra attempts after the first one fails
+    pub backoff_ms : u64,         // Wait before the first retry, doubled for each retry after
+    pub disconnect_after : usize, // Undeliverable messages in a row before the player is disconnected
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            retries : 3,
+            backoff_ms : 100,
+            disconnect_after : 3,
+        }
+    }
+}
+
+/// A message we gave up trying to deliver
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetter {
+    pub message : Value,
+    pub attempts : usize,
+    pub error : String, // Why the last attempt failed
+}
+
+/// How delivery to a player is going. Shared between the game and the mailer thread.
+#[derive(Debug, Default)]
+pub struct DeliveryStatus {
+    pub failures : usize,             // Failed attempts over the whole game
+    pub undelivered_in_a_row : usize, // Messages given up on since the last one that got through
+    pub disconnected : bool,          // Have we given up on this player?
+    pub dead_letters : Vec<DeadLetter>,
+}
+
+/// The messages a mailbox is still holding on to, and who's reading them
+#[derive(Debug, Default)]
+struct Kept {
+    first : usize, // Number of the oldest message kept
+    messages : VecDeque<Value>,
+    reader : Option<usize>, // The socket reading the mailbox, if one is. Otherwise anyone can poll it.
+    readers_seen : usize,   // Sockets that have read the mailbox, to number the next one
+    drop_unread : bool,     // Only keep messages while a socket's reading them?
+    backlog : Option<usize>, // Most messages to keep, dropping the oldest
+}
+
+/// Messages sent to a player, numbered from 0, for players to fetch themselves.
+/// Messages the player has already fetched are dropped with `drop_before`. Only one socket
+/// or poller reads a mailbox at a time, so nothing one of them fetched is dropped before
+/// the other gets it.
+#[derive(Debug, Default)]
+pub struct Mailbox {
+    kept : Mutex<Kept>,
+    arrived : Condvar,
+}
+
+impl Mailbox {
+    /// A mailbox that only keeps messages while a socket's reading it, for players who are
+    /// sent their messages somewhere else the rest of the time
+    pub fn only_while_read() -> Mailbox {
+        let mailbox = Mailbox::default();
+        mailbox.kept.lock().unwrap().drop_unread = true;
+        mailbox
+    }
+
+    /// A mailbox that only keeps the last `messages` messages, for readers who don't drop them
+    pub fn with_backlog(messages : usize) -> Mailbox {
+        let mailbox = Mailbox::default();
+        mailbox.kept.lock().unwrap().backlog = Some(messages);
+        mailbox
+    }
+
+    pub fn push(&self, message : Value) -> () {
+        {
+            let mut kept = self.kept.lock().unwrap();
+            if kept.drop_unread && kept.reader.is_none() {
+                // Nothing's kept, but the message still gets a number
+                kept.first += 1;
+            } else {
+                kept.messages.push_back(message);
+            }
+
+            if kept.backlog.map_or(false, |backlog| kept.messages.len() > backlog) {
+                kept.messages.pop_front();
+                kept.first += 1;
+            }
+        }
+        self.arrived.notify_all();
+    }
+
+    /// Messages numbered `since` onwards that are still kept. If there aren't any yet,
+    /// wait up to `timeout` for some.
+    pub fn wait_since(&self, since : usize, timeout : Duration) -> Vec<(usize, Value)> {
+        let deadline = Instant::now() + timeout;
+        let mut kept = self.kept.lock().unwrap();
+
+        while kept.first + kept.messages.len() <= since {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            kept = self.arrived.wait_timeout(kept, deadline - now).unwrap().0;
+        }
+
+        kept.messages.iter()
+                     .enumerate()
+                     .map(|(index, message)| (kept.first + index, message))
+                     .skip_while(|&(seq, _)| seq < since)
+                     .map(|(seq, message)| (seq, message.clone()))
+                     .collect()
+    }
+
+    /// Forget messages numbered before `seq`, once `reader` has sent them to the player.
+    /// `reader` is `None` for a poller. Returns false, without dropping anything, if someone
+    /// else is reading the mailbox.
+    pub fn drop_before(&self, reader : Option<usize>, seq : usize) -> bool {
+        let mut kept = self.kept.lock().unwrap();
+        if kept.reader != reader {
+            return false;
+        }
+
+        while kept.first < seq && kept.messages.pop_front().is_some() {
+            kept.first += 1;
+        }
+
+        true
+    }
+
+    /// Have a socket read the mailbox from now on, until it's detached. Returns the
+    /// reader's number, or `None` if another socket is already reading it.
+    pub fn attach(&self) -> Option<usize> {
+        let mut kept = self.kept.lock().unwrap();
+        if kept.reader.is_some() {
+            return None;
+        }
+
+        kept.readers_seen += 1;
+        kept.reader = Some(kept.readers_seen);
+        kept.reader
+    }
+
+    /// The socket reading the mailbox has closed
+    pub fn detach(&self, reader : usize) -> () {
+        let mut kept = self.kept.lock().unwrap();
+        if kept.reader != Some(reader) {
+            return;
+        }
+
+        kept.reader = None;
+        if kept.drop_unread {
+            kept.first += kept.messages.len();
+            kept.messages.clear();
+        }
+    }
+}
+
+/// POSTs messages to a player from a background thread, in the same order they were queued,
+/// or keeps them in their mailbox if they didn't give us an address. Players with an address
+/// get them in their mailbox too while they have a socket reading it.
+#[derive(Debug)]
+pub struct Mailer {
+    queue : Option<Sender<(Value, RetryPolicy)>>,
+    status : Arc<Mutex<DeliveryStatus>>,
+    mailbox : Arc<Mailbox>,
+}
+
+impl Mailer {
+    /// Start a mailer that delivers to `{address}/player`, or to the mailbox without an address
+    pub fn new(name : &str, address : Option<&str>) -> Mailer {
+        let status = Arc::new(Mutex::new(DeliveryStatus::default()));
+
+        let address = match address {
+            Some(address) => address,
+            None => {
+                return Mailer {
+                    queue : None,
+                    status : status,
+                    mailbox : Arc::new(Mailbox::default()),
+                };
+            },
+        };
+
+        let (queue, outbox) = channel::<(Value, RetryPolicy)>();
+
+        let name = name.to_owned();
+        let post_addr = address.to_owned() + "/player";
+        let thread_status = status.clone();
+
+        // The thread stops once the mailer is dropped and the queue is empty
+        thread::spawn(move || {
+            let client = reqwest::Client::new();
+
+            for (message, policy) in outbox {
+                // Don't hold up the queue retrying a player we've given up on
+                let retries = if thread_status.lock().unwrap().disconnected { 0 } else { policy.retries };
+
+                let mut attempts = 0;
+                let mut backoff = Duration::from_millis(policy.backoff_ms);
+                let result = loop {
+                    attempts += 1;
+
+                    let error = match post(&client, &post_addr, &message) {
+                        Ok(()) => break Ok(()),
+                        Err(error) => error,
+                    };
+
+                    log!("DEBUG - Couldn't deliver to {} (attempt {}): {}",name,attempts,error);
+                    thread_status.lock().unwrap().failures += 1;
+
+                    if attempts > retries {
+                        break Err(error);
+                    }
+
+                    thread::sleep(backoff);
+                    backoff = backoff * 2;
+                };
+
+                let mut status = thread_status.lock().unwrap();
+                match result {
+                    Ok(()) => {
+                        if status.disconnected {
+                            log!("DEBUG - {} is reachable again",name);
+                        }
+                        status.undelivered_in_a_row = 0;
+                        status.disconnected = false;
+                    },
+                    Err(error) => {
+                        status.undelivered_in_a_row += 1;
+                        status.dead_letters.push(DeadLetter {
+                            message : message,
+                            attempts : attempts,
+                            error : error,
+                        });
+
+                        if !status.disconnected && status.undelivered_in_a_row >= policy.disconnect_after {
+                            log!("DEBUG - Giving up on {}, marking as disconnected",name);
+                            status.disconnected = true;
+                        }
+                    },
+                }
+            }
+        });
+
+        Mailer {
+            queue : Some(queue),
+            status : status,
+            mailbox : Arc::new(Mailbox::only_while_read()),
+        }
+    }
+
+    /// Queue a message for delivery. This never waits on the network.
+    pub fn send(&self, message : Value, policy : RetryPolicy) -> () {
+        match self.queue {
+            Some(ref queue) => {
+                self.mailbox.push(message.clone());
+                if queue.send((message, policy)).is_err() {
+                    log!("DEBUG - Mailer thread has stopped, dropping message");
+                }
+            },
+            None => self.mailbox.push(message),
+        }
+    }
+
+    pub fn mailbox(&self) -> Arc<Mailbox> {
+        self.mailbox.clone()
+    }
+
+    pub fn is_disconnected(&self) -> bool {
+        self.status.lock().unwrap().disconnected
+    }
+
+    pub fn failures(&self) -> usize {
+        self.status.lock().unwrap().failures
+    }
+
+    pub fn dead_letters(&self) -> Vec<DeadLetter> {
+        self.status.lock().unwrap().dead_letters.clone()
+    }
+}
+
+/// Gets a game's events to its players, by POSTing them to their addresses or keeping
+/// them in their mailboxes, and to its spectators
+#[derive(Debug)]
+pub struct HttpSink {
+    policy : Mutex<RetryPolicy>,             // How hard we try to get messages to players
+    mailers : Mutex<HashMap<usize, Mailer>>, // Delivers messages to each player in the background
+    spectator_feed : Arc<Mailbox>,           // The latest messages sent to all players, for spectators
+}
+
+impl Default for HttpSink {
+    fn default() -> HttpSink {
+        HttpSink {
+            policy : Mutex::new(RetryPolicy::default()),
+            mailers : Mutex::new(HashMap::new()),
+            spectator_feed : Arc::new(Mailbox::with_backlog(SPECTATOR_BACKLOG)),
+        }
+    }
+}
+
+impl HttpSink {
+    /// Start delivering to a player who's joined the game.
+    /// Players without an address have to fetch their messages from their mailbox.
+    pub fn add_player(&self, player_id : usize, name : &str, address : Option<&str>) -> () {
+        self.mailers.lock().unwrap().insert(player_id, Mailer::new(name, address));
+    }
+
+    /// Retry failed messages to players this many times before giving up on them
+    pub fn set_retries(&self, retries : usize) -> () {
+        self.policy.lock().unwrap().retries = retries;
+        log!("CONFIG - Setting push retries to {}",retries);
+    }
+
+    /// Wait this long before retrying a failed message. The wait doubles with each retry.
+    pub fn set_backoff(&self, millis : u64) -> () {
+        self.policy.lock().unwrap().backoff_ms = millis;
+        log!("CONFIG - Setting push backoff to {}ms",millis);
+    }
+
+    /// Treat a player as disconnected after this many undeliverable messages in a row
+    pub fn set_disconnect_after(&self, messages : usize) -> () {
+        self.policy.lock().unwrap().disconnect_after = messages;
+        log!("CONFIG - Setting disconnect after {} undelivered messages",messages);
+    }
+
+    /// Messages sent to a player that they haven't fetched yet. Players with an address only
+    /// have messages kept here while they have a socket reading it.
+    pub fn mailbox(&self, player_id : usize) -> Arc<Mailbox> {
+        self.mailers.lock().unwrap()[&player_id].mailbox()
+    }
+
+    /// The latest messages sent to all players
+    pub fn spectator_feed(&self) -> Arc<Mailbox> {
+        self.spectator_feed.clone()
+    }
+
+    /// Messages that couldn't be delivered to a player
+    pub fn dead_letters(&self, player_id : usize) -> Vec<DeadLetter> {
+        self.mailers.lock().unwrap()[&player_id].dead_letters()
+    }
+
+    /// Number of failed attempts to deliver messages to a player
+    pub fn failures(&self, player_id : usize) -> usize {
+        self.mailers.lock().unwrap()[&player_id].failures()
+    }
+}
+
+impl NotificationSink for HttpSink {
+    /// Queue a message for a player. Delivery happens in the background, so this never blocks.
+    fn player_event(&self, player_id : usize, event : &GameEvent) -> () {
+        let message = serde_json::to_value(event).unwrap();
+        let policy = *self.policy.lock().unwrap();
+
+        self.mailers.lock().unwrap()[&player_id].send(message, policy);
+    }
+
+    fn public_event(&self, event : &GameEvent) -> () {
+        self.spectator_feed.push(serde_json::to_value(event).unwrap());
+    }
+
+    /// Has a player stopped responding to messages?
+    fn is_disconnected(&self, player_id : usize) -> bool {
+        self.mailers.lock().unwrap()[&player_id].is_disconnected()
+    }
+
+    fn name(&self) -> &'static str {
+        "HttpSink"
+    }
+}
+
+/// Make a single attempt at POSTing a message
+fn post(client : &reqwest::Client, post_addr : &str, message : &Value) -> Result<(), String> {
+    let mut header = Headers::new();
+    header.set(
+        ContentType::json()
+    );
+
+    match client.post(post_addr).headers(header).json(message).send() {
+        Ok(ref response) if response.status().is_success() => Ok(()),
+        Ok(response) => Err(format!("Player responded {}",response.status())),
+        Err(err) => Err(err.to_string()),
+    }
+}
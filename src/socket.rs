@@ -0,0 +1,217 @@
+use ws;
+use ws::{CloseCode, Handshake, Handler, Message};
+use serde_json;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::Duration;
+use uuid::Uuid;
+use delivery::Mailbox;
+use gateau::error::GameError;
+use {GameMap, PlayerMessage, apply_move, authenticate_query, error_json};
+
+/// How long a connection's forwarding thread waits for messages before checking
+/// whether the connection has closed
+const FORWARD_WAIT_SECS : u64 = 5;
+
+/// A move sent over a player's socket. The game and secret ID come from the connection.
+#[derive(Deserialize)]
+struct SocketMove {
+    action : String,
+    #[serde(default)]
+    value  : usize,
+}
+
+/// One WebSocket connection, from either a player or a spectator
+struct Connection {
+    out : ws::Sender,
+    games : GameMap,
+    game_id : String,
+    secret_id : Option<Uuid>, // Only players have one
+    reader : Option<(Arc<Mailbox>, usize)>, // The player's mailbox, and our number as its reader
+    closed : Arc<AtomicBool>,
+}
+
+/// Start serving WebSocket connections in the background, once the address is bound.
+///
+/// Players connect to `/?game_id=some_id&secret_id=...` to be sent everything they'd be sent
+/// at `/player`, and can send moves back. Spectators connect to `/?game_id=some_id` and are
+/// sent everything that's sent to all players.
+pub fn serve(address : &str, games : GameMap) -> Result<(), String> {
+    let (bound_tx, bound) = channel();
+    let address = address.to_owned();
+
+    thread::spawn(move || {
+        let socket = ws::WebSocket::new(move |out| {
+            Connection {
+                out : out,
+                games : games.clone(),
+                game_id : String::new(),
+                secret_id : None,
+                reader : None,
+                closed : Arc::new(AtomicBool::new(false)),
+            }
+        }).and_then(|socket| socket.bind(address.as_str()));
+
+        let socket = match socket {
+            Ok(socket) => socket,
+            Err(err) => {
+                let _ = bound_tx.send(Err(format!("{:?}", err)));
+                return;
+            },
+        };
+
+        log!("DEBUG - Serving WebSockets on {}",address);
+        let _ = bound_tx.send(Ok(()));
+
+        if let Err(err) = socket.run() {
+            log!("DEBUG - WebSocket server stopped: {}",err);
+        }
+    });
+
+    bound.recv().unwrap_or_else(|_| Err("WebSocket thread stopped".to_string()))
+}
+
+impl Handler for Connection {
+    fn on_open(&mut self, shake : Handshake) -> ws::Result<()> {
+        let (game_id, secret_id) = parse_query(shake.request.resource());
+
+        let game_id = match game_id {
+            Some(game_id) => game_id,
+            None => return self.out.close_with_reason(CloseCode::Policy, GameError::UnknownGame.reason()),
+        };
+
+        let mailbox = {
+            let games = self.games.read().unwrap();
+
+            let table = match games.get(&game_id) {
+                Some(table) => table,
+                None => return self.out.close_with_reason(CloseCode::Policy, GameError::UnknownGame.reason()),
+            };
+
+            match secret_id {
+                Some(ref secret_id) => {
+                    match authenticate_query(&table.game, &game_id, secret_id) {
+                        Ok(player_id) => {
+                            self.secret_id = Some(table.game.players[&player_id].secret_id);
+                            table.delivery.mailbox(player_id)
+                        },
+                        Err(error) => return self.out.close_with_reason(CloseCode::Policy, error.reason()),
+                    }
+                },
+                None => table.delivery.spectator_feed(),
+            }
+        };
+
+        // Messages sent down one player's socket would never reach another of theirs
+        let reader = if self.secret_id.is_some() {
+            match mailbox.attach() {
+                Some(reader) => {
+                    self.reader = Some((mailbox.clone(), reader));
+                    Some(reader)
+                },
+                None => return self.out.close_with_reason(CloseCode::Policy, GameError::AlreadyConnected.reason()),
+            }
+        } else {
+            None
+        };
+
+        self.game_id = game_id;
+
+        log!("DEBUG - WebSocket opened for game {} ({})",self.game_id,
+             if self.secret_id.is_some() { "player" } else { "spectator" });
+
+        forward(mailbox, reader, self.out.clone(), self.closed.clone());
+
+        Ok(())
+    }
+
+    fn on_message(&mut self, msg : Message) -> ws::Result<()> {
+        let secret_id = match self.secret_id {
+            Some(secret_id) => secret_id,
+            // Spectators can't make moves
+            None => return self.out.send(error_json(GameError::UnknownSecret).to_string()),
+        };
+
+        let socket_move = msg.as_text()
+                             .ok()
+                             .and_then(|text| serde_json::from_str::<SocketMove>(text).ok());
+        let socket_move = match socket_move {
+            Some(socket_move) => socket_move,
+            None => return self.out.send(error_json(GameError::InvalidAction).to_string()),
+        };
+
+        let action = PlayerMessage {
+            game_id : self.game_id.clone(),
+            secret_id : secret_id,
+            action : socket_move.action,
+            value : socket_move.value,
+        };
+
+        let result = {
+            let mut games = self.games.write().unwrap();
+            match games.get_mut(&self.game_id) {
+                Some(table) => apply_move(&mut table.game, &action),
+                None => Err(GameError::UnknownGame),
+            }
+        };
+
+        let response = match result {
+            Ok(()) => json!({
+                "status" : "ok",
+            }),
+            Err(error) => error_json(error),
+        };
+
+        self.out.send(response.to_string())
+    }
+
+    fn on_close(&mut self, _code : CloseCode, _reason : &str) {
+        self.closed.store(true, Ordering::SeqCst);
+
+        if let Some((ref mailbox, reader)) = self.reader {
+            mailbox.detach(reader);
+        }
+    }
+}
+
+/// Send everything that arrives in the mailbox down the socket, in order, until it closes.
+/// A player's socket is its mailbox's `reader`, and drops messages once they're sent. The
+/// spectator feed is shared, so it keeps them.
+fn forward(mailbox : Arc<Mailbox>, reader : Option<usize>, out : ws::Sender, closed : Arc<AtomicBool>) -> () {
+    thread::spawn(move || {
+        let mut next = 0;
+
+        while !closed.load(Ordering::SeqCst) {
+            for (seq, message) in mailbox.wait_since(next, Duration::from_secs(FORWARD_WAIT_SECS)) {
+                if out.send(message.to_string()).is_err() {
+                    return;
+                }
+                next = seq + 1;
+            }
+
+            if reader.is_some() && !mailbox.drop_before(reader, next) {
+                return;
+            }
+        }
+    });
+}
+
+/// Pull the game ID and secret ID out of a resource like `/?game_id=..&secret_id=..`
+fn parse_query(resource : &str) -> (Option<String>, Option<String>) {
+    let mut game_id = None;
+    let mut secret_id = None;
+
+    let query = resource.splitn(2, '?').nth(1).unwrap_or("");
+    for pair in query.split('&') {
+        let mut parts = pair.splitn(2, '=');
+        match (parts.next(), parts.next()) {
+            (Some("game_id"), Some(value)) => game_id = Some(value.to_string()),
+            (Some("secret_id"), Some(value)) => secret_id = Some(value.to_string()),
+            _ => (),
+        }
+    }
+
+    (game_id, secret_id)
+}